@@ -0,0 +1,25 @@
+extern crate turing;
+
+mod common;
+
+use std::time::Instant;
+use turing::{ Fuel, Tape };
+
+fn main() {
+    let t = common::beaver();
+
+    let start = Instant::now();
+    let plain = t.run(Tape::new(0, None));
+    let plain_time = start.elapsed();
+    println!("{} steps", plain.stats().steps);
+    println!("plain:    {:>8.3}s", plain_time.as_secs_f64());
+
+    for size in [1, 2, 3, 6] {
+        let start = Instant::now();
+        let blocks = t.blocks(size).run(Tape::new(0, None), Fuel::unlimited());
+        let blocks_time = start.elapsed();
+
+        common::assert_agree(&plain, &blocks, &format!("blocks of {}", size));
+        println!("blocks {}: {:>8.3}s ({:.1}x)", size, blocks_time.as_secs_f64(), plain_time.as_secs_f64() / blocks_time.as_secs_f64());
+    }
+}
@@ -0,0 +1,22 @@
+use turing::{ Halt, Tape, Turing, turing, Dir::* };
+
+/// The five state busy beaver champion, which halts after 47,176,870 steps.
+pub fn beaver() -> Turing<Tape<u8>, char> {
+    turing!(0u8;'A';'H';
+        ('A') { 0 => (Right, 1, 'B') 1 => (Left , 1, 'C') },
+        ('B') { 0 => (Right, 1, 'C') 1 => (Right, 1, 'B') },
+        ('C') { 0 => (Right, 1, 'D') 1 => (Left , 0, 'E') },
+        ('D') { 0 => (Left , 1, 'A') 1 => (Left , 1, 'D') },
+        ('E') { 0 => (Right, 1, 'H') 1 => (Left , 0, 'A') },
+    )
+}
+
+/// Panics unless both runs accepted with the same tape after the same number of steps.
+pub fn assert_agree(plain: &Halt<Tape<u8>, char>, other: &Halt<Tape<u8>, char>, engine: &str) {
+    match (plain, other) {
+        (Halt::Accepted { space: a, stats: s, .. }, Halt::Accepted { space: b, stats: t, .. }) => {
+            assert!(a == b && s == t, "the plain engine and {} disagree", engine);
+        }
+        _ => panic!("the plain engine and {} disagree", engine),
+    }
+}
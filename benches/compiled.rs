@@ -0,0 +1,26 @@
+extern crate turing;
+
+mod common;
+
+use std::time::Instant;
+use turing::{ Fuel, Tape };
+
+fn main() {
+    let t = common::beaver();
+
+    let start = Instant::now();
+    let plain = t.run(Tape::new(0, None));
+    let plain_time = start.elapsed();
+
+    let start = Instant::now();
+    let compiled = t.compile().run(Tape::new(0, None), Fuel::unlimited());
+    let compiled_time = start.elapsed();
+
+    common::assert_agree(&plain, &compiled, "the compiled engine");
+
+    let steps = plain.stats().steps;
+    println!("{} steps", steps);
+    println!("plain:    {:>8.3}s ({:.1} ns/step)", plain_time.as_secs_f64(), plain_time.as_nanos() as f64 / steps as f64);
+    println!("compiled: {:>8.3}s ({:.1} ns/step)", compiled_time.as_secs_f64(), compiled_time.as_nanos() as f64 / steps as f64);
+    println!("speedup:  {:>8.1}x", plain_time.as_secs_f64() / compiled_time.as_secs_f64());
+}
@@ -0,0 +1,40 @@
+// Copies a word over {a, b}, so `_ab` becomes `_ab_ab`.
+// The input must start with a blank.
+blank: _
+initial: 0
+accept: 9
+
+0 _ -> 1 _ R
+
+1 a -> 2 A R
+1 b -> 4 B R
+1 _ -> 8 _ L
+
+2 a -> 2 a R
+2 b -> 2 b R
+2 _ -> 3 _ R
+
+3 a -> 3 a R
+3 b -> 3 b R
+3 _ -> 6 a L
+
+4 a -> 4 a R
+4 b -> 4 b R
+4 _ -> 5 _ R
+
+5 a -> 5 a R
+5 b -> 5 b R
+5 _ -> 6 b L
+
+6 a -> 6 a L
+6 b -> 6 b L
+6 _ -> 7 _ L
+
+7 a -> 7 a L
+7 b -> 7 b L
+7 A -> 1 A R
+7 B -> 1 B R
+
+8 A -> 8 a L
+8 B -> 8 b L
+8 _ -> 9 _ S
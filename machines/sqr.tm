@@ -0,0 +1,19 @@
+// Decides whether a unary number is a perfect square, using a second tape as a counter.
+// Run with --tapes 2.
+blank: _
+initial: Start
+accept: Done
+reject: Reject
+
+Start 1_ -> Reset 1_ RS
+Start __ -> Done __ SS
+
+Bump 11 -> Bump 11 RR
+Bump 1_ -> Reset 11 RS
+Bump _1 -> Reject _1 SS
+Bump __ -> Reject __ SS
+
+Reset 11 -> Reset 11 RL
+Reset 1_ -> Bump 1_ RR
+Reset __ -> Done __ SR
+Reset _1 -> Reject _1 SS
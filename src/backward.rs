@@ -0,0 +1,273 @@
+//! Proving that a single-tape machine never halts from a blank tape by reasoning backwards.
+//!
+//! A machine halts by entering an accepting or rejecting state, or by reading a symbol it has
+//! no transition for in its state. Starting from those halting configurations, where only the
+//! cell under the head is known, every transition that leads into one gives a predecessor that
+//! knows one more cell, unless the transition would have written a different symbol than the
+//! one known to be there. If searching backwards like this runs out of predecessors without
+//! coming across a configuration that could be the blank start, no run from a blank tape halts.
+
+use std::collections::{ BTreeMap, HashMap, HashSet };
+use std::hash::Hash;
+
+use crate::{ Dir, Tape, Turing };
+
+/// A configuration known only on some cells, given relative to the head.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Partial<Sym, Mem> {
+    pub mem: Mem,
+    pub cells: BTreeMap<isize, Sym>,
+}
+
+/// The configurations explored backwards from every halting configuration. Each configuration
+/// lists the indices of its predecessors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate<Sym, Mem> {
+    pub configs: Vec<(Partial<Sym, Mem>, Vec<usize>)>,
+}
+
+fn offset(dir: &Dir) -> isize {
+    match dir {
+        Dir::Left => -1,
+        Dir::Stay => 0,
+        Dir::Right => 1,
+    }
+}
+
+impl<Sym, Mem> Turing<Tape<Sym>, Mem>
+    where Sym: Clone + Eq + Hash, Mem: Clone + Eq + Hash {
+    /// Searches backwards from the halting configurations for at most `depth` steps. Returns a
+    /// certificate that the machine never halts from a blank tape if every branch dies out, and
+    /// `None` if a branch could have started on a blank tape, is still going at `depth`, or the
+    /// search comes across more than `limit` configurations.
+    pub fn prove_non_halting(&self, depth: usize, limit: usize) -> Option<Certificate<Sym, Mem>> {
+        let mut configs: Vec<(Partial<Sym, Mem>, Vec<usize>)> = Vec::new();
+        let mut ids = HashMap::new();
+        let mut frontier = Vec::new();
+        for partial in self.halting() {
+            if !ids.contains_key(&partial) {
+                ids.insert(partial.clone(), configs.len());
+                frontier.push(configs.len());
+                configs.push((partial, Vec::new()));
+            }
+        }
+
+        for level in 0..=depth {
+            let mut next = Vec::new();
+            for i in frontier {
+                if self.could_start(&configs[i].0) {
+                    return None;
+                }
+                let predecessors = self.predecessors(&configs[i].0);
+                if level == depth && !predecessors.is_empty() {
+                    return None;
+                }
+                for partial in predecessors {
+                    let id = *ids.entry(partial.clone()).or_insert_with(|| {
+                        next.push(configs.len());
+                        configs.push((partial, Vec::new()));
+                        configs.len() - 1
+                    });
+                    configs[i].1.push(id);
+                }
+                if configs.len() > limit {
+                    return None;
+                }
+            }
+            if next.is_empty() {
+                return Some(Certificate { configs });
+            }
+            frontier = next;
+        }
+        None
+    }
+
+    fn halting(&self) -> Vec<Partial<Sym, Mem>> {
+        let mut halting: Vec<Partial<Sym, Mem>> = self.accepting.iter().chain(&self.rejecting)
+            .map(|mem| Partial { mem: mem.clone(), cells: BTreeMap::new() })
+            .collect();
+        let symbols = self.symbols();
+        for mem in self.states() {
+            if self.accepting.contains(mem) || self.rejecting.contains(mem) {
+                continue;
+            }
+            for sym in &symbols {
+                if !self.map.contains_key(&((*sym).clone(), mem.clone())) {
+                    halting.push(Partial { mem: mem.clone(), cells: Some((0, (*sym).clone())).into_iter().collect() });
+                }
+            }
+        }
+        halting
+    }
+
+    /// The symbols a run from a blank tape can come across.
+    fn symbols(&self) -> HashSet<&Sym> {
+        let mut symbols: HashSet<&Sym> = self.map.iter().flat_map(|((sym, _), (_, sym_new, _))| vec![sym, sym_new]).collect();
+        symbols.insert(&self.default);
+        symbols
+    }
+
+    fn states(&self) -> HashSet<&Mem> {
+        let mut states: HashSet<&Mem> = self.map.iter().flat_map(|((_, mem), (_, _, mem_new))| vec![mem, mem_new]).collect();
+        states.insert(&self.initial);
+        states
+    }
+
+    fn could_start(&self, partial: &Partial<Sym, Mem>) -> bool {
+        partial.mem == self.initial && partial.cells.values().all(|sym| *sym == self.default)
+    }
+
+    fn predecessors(&self, partial: &Partial<Sym, Mem>) -> Vec<Partial<Sym, Mem>> {
+        self.map.iter()
+            .filter(|(_, (_, _, mem_new))| *mem_new == partial.mem)
+            .filter_map(|((sym, mem), (dir, sym_new, _))| predecessor(partial, sym, mem, dir, sym_new))
+            .collect()
+    }
+}
+
+/// The configuration before taking the transition that reads `sym` in `mem`, writes `sym_new`
+/// and moves in `dir` to reach `partial`, or `None` if `partial` rules it out.
+fn predecessor<Sym: Clone + Eq, Mem: Clone>(partial: &Partial<Sym, Mem>, sym: &Sym, mem: &Mem, dir: &Dir, sym_new: &Sym) -> Option<Partial<Sym, Mem>> {
+    let shift = offset(dir);
+    if partial.cells.get(&-shift).is_some_and(|known| known != sym_new) {
+        return None;
+    }
+    let mut cells: BTreeMap<isize, Sym> = partial.cells.iter()
+        .filter(|(&cell, _)| cell != -shift)
+        .map(|(&cell, sym)| (cell + shift, sym.clone()))
+        .collect();
+    cells.insert(0, sym.clone());
+    Some(Partial { mem: mem.clone(), cells })
+}
+
+impl<Sym, Mem> Certificate<Sym, Mem>
+    where Sym: Clone + Eq + Hash, Mem: Clone + Eq + Hash {
+    /// Checks that the certificate proves `turing` never halts from a blank tape, without
+    /// relying on the search that produced it.
+    ///
+    /// A configuration covers a concrete one when they are in the same state and agree on every
+    /// cell the former knows. The certificate must cover each way to halt, and for each of its
+    /// configurations and each transition into its state that does not contradict it, one of
+    /// the listed predecessors must cover the configuration before the transition. Then the
+    /// configurations of any halting run are covered all the way back to its start, so a
+    /// certificate where none could be the blank start shows there is no such run.
+    pub fn check(&self, turing: &Turing<Tape<Sym>, Mem>) -> Result<(), String> {
+        let covers = |partial: &Partial<Sym, Mem>, mem: &Mem, cells: &BTreeMap<isize, Sym>| {
+            partial.mem == *mem && partial.cells.iter().all(|(cell, sym)| cells.get(cell) == Some(sym))
+        };
+
+        let finals = turing.accepting.iter().chain(&turing.rejecting);
+        let mut halting: Vec<(Mem, BTreeMap<isize, Sym>)> = finals.clone().map(|mem| (mem.clone(), BTreeMap::new())).collect();
+        let mut symbols = vec![&turing.default];
+        let mut states = vec![&turing.initial];
+        for ((sym, mem), (_, sym_new, mem_new)) in &turing.map {
+            symbols.extend([sym, sym_new]);
+            states.extend([mem, mem_new]);
+        }
+        for mem in states {
+            for &sym in &symbols {
+                if !finals.clone().any(|f| f == mem) && !turing.map.contains_key(&(sym.clone(), mem.clone())) {
+                    halting.push((mem.clone(), Some((0, sym.clone())).into_iter().collect()));
+                }
+            }
+        }
+        for (mem, cells) in &halting {
+            if !self.configs.iter().any(|(partial, _)| covers(partial, mem, cells)) {
+                return Err("a way to halt is not covered".to_owned());
+            }
+        }
+
+        for (i, (partial, listed)) in self.configs.iter().enumerate() {
+            if partial.mem == turing.initial && partial.cells.values().all(|sym| *sym == turing.default) {
+                return Err(format!("configuration {} could be the blank start", i));
+            }
+            let listed = listed.iter()
+                .map(|&j| self.configs.get(j).map(|(p, _)| p).ok_or_else(|| format!("configuration {} lists {}, which does not exist", i, j)))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for ((sym, mem), (dir, sym_new, mem_new)) in &turing.map {
+                if *mem_new != partial.mem {
+                    continue;
+                }
+                // The head moved from `-shift` to 0, after writing `sym_new` there.
+                let shift = offset(dir);
+                if partial.cells.get(&-shift).is_some_and(|known| known != sym_new) {
+                    continue;
+                }
+                let mut before: BTreeMap<isize, Sym> = partial.cells.iter().map(|(&cell, sym)| (cell + shift, sym.clone())).collect();
+                before.insert(0, sym.clone());
+                if !listed.iter().any(|p| covers(p, mem, &before)) {
+                    return Err(format!("configuration {} has a predecessor that is not covered", i));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{ Certificate, Partial };
+    use crate::{ Tape, Turing };
+    use crate::beaver::{ Enumeration, Outcome };
+
+    type Proven = (Turing<Tape<u8>, u8>, Certificate<u8, u8>);
+
+    /// Certificates for the two state candidates that are proven never to halt.
+    fn proven() -> Vec<Proven> {
+        Enumeration::new(2, 2, 100)
+            .filter(|c| matches!(c.outcome, Outcome::Undecided))
+            .filter_map(|c| c.machine.prove_non_halting(20, 10_000).map(|cert| (c.machine, cert)))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_certificates_from_the_search() {
+        let proven = proven();
+        assert!(proven.iter().any(|(_, cert)| cert.configs.len() > 2));
+        for (t, cert) in &proven {
+            assert_eq!(cert.check(t), Ok(()));
+        }
+    }
+
+    #[test]
+    fn proves_nothing_about_halting_machines() {
+        for c in Enumeration::new(2, 2, 100).filter(|c| matches!(c.outcome, Outcome::Halted { .. })) {
+            assert!(c.machine.prove_non_halting(20, 10_000).is_none());
+        }
+    }
+
+    #[test]
+    fn rejects_a_certificate_missing_a_configuration() {
+        for (t, cert) in proven() {
+            for removed in 0..cert.configs.len() {
+                let configs = cert.configs.iter().enumerate()
+                    .filter(|&(i, _)| i != removed)
+                    .map(|(_, (partial, listed))| {
+                        let listed = listed.iter().filter(|&&j| j != removed).map(|&j| if j > removed { j - 1 } else { j }).collect();
+                        (partial.clone(), listed)
+                    })
+                    .collect();
+                assert!(Certificate { configs }.check(&t).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_a_dangling_predecessor() {
+        let (t, mut cert) = proven().swap_remove(0);
+        let dangling = cert.configs.len();
+        cert.configs[0].1.push(dangling);
+        assert!(cert.check(&t).unwrap_err().contains("does not exist"));
+    }
+
+    #[test]
+    fn rejects_the_blank_start() {
+        let (t, mut cert) = proven().swap_remove(0);
+        let start = Partial { mem: *t.initial(), cells: Some((0, *t.blank())).into_iter().collect::<BTreeMap<_, _>>() };
+        cert.configs.push((start, Vec::new()));
+        assert!(cert.check(&t).unwrap_err().contains("blank start"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Busy beaver candidates, enumerated in tree normal form.
+//!
+//! Machines have states `0..states` and symbols `0..symbols`, with `0` the initial state and the
+//! blank. A machine starts with no transitions and runs on a blank tape until it reads a symbol
+//! it has no transition for. That gives a candidate which halts there, by writing `1` and moving
+//! right into the accepting state `states`, and one child for every way to define the missing
+//! transition, each of which carries on from where its parent stopped. New states and symbols
+//! are introduced in order and the first transition is always `0 0 -> 1 1 R`, so machines that
+//! only differ by renaming states or symbols or by mirroring the tape are enumerated once.
+
+use std::collections::HashMap;
+
+use crate::{ Dir, Fuel, Halt, Tape, Transitions, Turing };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The machine halts after `steps` steps, the last being the halting transition, leaving
+    /// `nonblanks` cells that are not blank.
+    Halted { steps: usize, nonblanks: usize },
+    /// The machine is still running after the step limit.
+    Undecided,
+}
+
+pub struct Candidate {
+    pub machine: Turing<Tape<u8>, u8>,
+    pub outcome: Outcome,
+}
+
+/// A partially defined machine, paused where it needs the transition it has not got.
+struct Node {
+    map: Transitions<Tape<u8>, u8>,
+    tape: Tape<u8>,
+    state: u8,
+    steps: usize,
+    max_state: u8,
+    max_sym: u8,
+}
+
+/// An iterator over the candidates with the given number of states and symbols, in depth-first
+/// order, each run for at most `limit` steps.
+pub struct Enumeration {
+    states: u8,
+    symbols: u8,
+    limit: usize,
+    stack: Vec<Node>,
+}
+
+impl Enumeration {
+    pub fn new(states: u8, symbols: u8, limit: usize) -> Self {
+        assert!((1..u8::MAX).contains(&states), "expected between 1 and 254 states");
+        assert!(symbols >= 2, "expected at least 2 symbols");
+        let root = Node { map: HashMap::new(), tape: Tape::new(0, None), state: 0, steps: 0, max_state: 0, max_sym: 0 };
+        Enumeration { states, symbols, limit, stack: vec![root] }
+    }
+
+    fn machine(&self, map: Transitions<Tape<u8>, u8>) -> Turing<Tape<u8>, u8> {
+        Turing::new(map, 0, 0, Some(self.states), None)
+    }
+
+    /// Pushes a child for each way to define the transition for `sym` in `state`, so they are
+    /// popped in order.
+    fn branch(&mut self, node: &Node, tape: Tape<u8>, state: u8, sym: u8, steps: usize) {
+        if node.map.len() + 1 == self.states as usize * self.symbols as usize {
+            return;
+        }
+
+        let mut actions = Vec::new();
+        if node.map.is_empty() {
+            actions.push((Dir::Right, 1, 1.min(self.states - 1)));
+        } else {
+            for write in 0..=(node.max_sym + 1).min(self.symbols - 1) {
+                for dir in [Dir::Left, Dir::Right] {
+                    for next in 0..=(node.max_state + 1).min(self.states - 1) {
+                        actions.push((dir, write, next));
+                    }
+                }
+            }
+        }
+
+        for (dir, write, next) in actions.into_iter().rev() {
+            let mut map = node.map.clone();
+            map.insert((sym, state), (dir, write, next));
+            self.stack.push(Node {
+                map,
+                tape: tape.clone(),
+                state,
+                steps,
+                max_state: node.max_state.max(next),
+                max_sym: node.max_sym.max(write),
+            });
+        }
+    }
+}
+
+impl Iterator for Enumeration {
+    type Item = Candidate;
+
+    fn next(&mut self) -> Option<Candidate> {
+        let node = self.stack.pop()?;
+        let fuel = Fuel::steps(self.limit.saturating_sub(node.steps));
+        let halt = self.machine(node.map.clone()).resume(node.tape.clone(), node.state, fuel);
+        match halt {
+            Halt::Stuck { space, mem, sym, stats } => {
+                let steps = node.steps + stats.steps;
+                let nonblanks = space.cells().filter(|&&s| s != 0).count() + (sym == 0) as usize;
+                self.branch(&node, space, mem, sym, steps);
+
+                let mut map = node.map;
+                map.insert((sym, mem), (Dir::Right, 1, self.states));
+                Some(Candidate { machine: self.machine(map), outcome: Outcome::Halted { steps: steps + 1, nonblanks } })
+            }
+            Halt::OutOfFuel { .. } => Some(Candidate { machine: self.machine(node.map), outcome: Outcome::Undecided }),
+            Halt::Accepted { .. } | Halt::Rejected { .. } => unreachable!("candidates only halt on missing transitions"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ Enumeration, Outcome };
+
+    /// The most steps and the most nonblank cells of a halting candidate.
+    fn champions(states: u8, symbols: u8, limit: usize) -> (usize, usize) {
+        Enumeration::new(states, symbols, limit)
+            .filter_map(|c| match c.outcome {
+                Outcome::Halted { steps, nonblanks } => Some((steps, nonblanks)),
+                Outcome::Undecided => None,
+            })
+            .fold((0, 0), |(s, n), (steps, nonblanks)| (s.max(steps), n.max(nonblanks)))
+    }
+
+    #[test]
+    fn finds_the_known_busy_beavers() {
+        assert_eq!(champions(2, 2, 100), (6, 4));
+        assert_eq!(champions(3, 2, 100), (21, 6));
+        assert_eq!(champions(2, 3, 100), (38, 9));
+    }
+
+    #[test]
+    fn halting_candidates_halt_as_reported() {
+        for candidate in Enumeration::new(3, 2, 100) {
+            if let Outcome::Halted { steps, nonblanks } = candidate.outcome {
+                let halt = candidate.machine.run_with_limit(crate::Tape::new(0, None), 100);
+                assert!(halt.is_accepted());
+                assert_eq!(halt.stats().steps, steps);
+                assert_eq!(halt.space().cells().filter(|&&s| s != 0).count(), nonblanks);
+            }
+        }
+    }
+}
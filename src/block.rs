@@ -0,0 +1,376 @@
+//! Simulating a single-tape machine a block of cells at a time.
+//!
+//! The tape is cut into blocks of `size` cells, stored as runs of equal blocks on either side of
+//! the block under the head. Running the machine from a cell of a block until the head leaves it
+//! or the machine halts gives a macro step, which is remembered for the next time the machine is
+//! in that state on that cell of an equal block. When the head leaves a block into a run of
+//! blocks that the macro step for the same state takes it straight through, the whole run is
+//! crossed at once.
+
+use std::collections::{ HashMap, VecDeque };
+use std::hash::Hash;
+
+use crate::{ Dir, Fuel, Halt, Stats, Tape, Turing };
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Exit {
+    Left,
+    Right,
+    /// The machine stopped with the head on this cell of the block.
+    Inside(usize),
+}
+
+/// What a stretch of steps inside one block does. `lo` and `hi` bound the cells it visits,
+/// relative to the start of the block, which includes the cell it steps out onto.
+#[derive(Clone)]
+struct Macro<Mem> {
+    block: u32,
+    mem: Mem,
+    exit: Exit,
+    steps: usize,
+    lo: isize,
+    hi: isize,
+}
+
+/// A single-tape machine run a block of cells at a time. It stops in exactly the configuration
+/// and after exactly as many steps as the `Turing` it was made from.
+pub struct Blocks<'a, Sym: Clone + Eq, Mem> {
+    turing: &'a Turing<Tape<Sym>, Mem>,
+    size: usize,
+    blocks: Vec<Vec<Sym>>,
+    block_ids: HashMap<Vec<Sym>, u32>,
+    macros: HashMap<(u32, Mem, usize), Macro<Mem>>,
+}
+
+impl<Sym, Mem> Turing<Tape<Sym>, Mem>
+    where Sym: Clone + Eq + Hash, Mem: Clone + Eq + Hash {
+    pub fn blocks(&self, size: usize) -> Blocks<'_, Sym, Mem> {
+        assert!(size > 0, "expected a positive block size");
+        Blocks { turing: self, size, blocks: Vec::new(), block_ids: HashMap::new(), macros: HashMap::new() }
+    }
+}
+
+/// The state of a run. The head is on cell `offset` of block `index`, the blocks next to it are
+/// at the ends of `left` and `right`, and every block past those is blank. `lo` and `hi` bound
+/// the cells the plain engine would have allocated.
+struct Run<Mem> {
+    left: Vec<(u32, usize)>,
+    right: Vec<(u32, usize)>,
+    block: u32,
+    index: isize,
+    offset: usize,
+    mem: Mem,
+    steps: usize,
+    lo: isize,
+    hi: isize,
+}
+
+impl<Mem> Run<Mem> {
+    fn stats(&self) -> Stats {
+        Stats { steps: self.steps, cells: (self.hi - self.lo + 1) as usize }
+    }
+}
+
+fn push(stack: &mut Vec<(u32, usize)>, block: u32, count: usize) {
+    match stack.last_mut() {
+        Some((top, n)) if *top == block => *n += count,
+        _ => stack.push((block, count)),
+    }
+}
+
+fn pop(stack: &mut Vec<(u32, usize)>, blank: u32) -> u32 {
+    match stack.last_mut() {
+        Some((block, n)) if *n > 1 => {
+            *n -= 1;
+            *block
+        }
+        Some(&mut (block, _)) => {
+            stack.pop();
+            block
+        }
+        None => blank,
+    }
+}
+
+impl<'a, Sym, Mem> Blocks<'a, Sym, Mem>
+    where Sym: Clone + Eq + Hash, Mem: Clone + Eq + Hash {
+    pub fn run(&mut self, tape: Tape<Sym>, fuel: Fuel) -> Halt<Tape<Sym>, Mem> {
+        self.resume(tape, self.turing.initial.clone(), fuel)
+    }
+
+    pub fn resume(&mut self, tape: Tape<Sym>, mem: Mem, fuel: Fuel) -> Halt<Tape<Sym>, Mem> {
+        let size = self.size as isize;
+        let bounds = tape.bounds();
+        let head = tape.head();
+        let index = head.div_euclid(size);
+        let mut blocks = Vec::new();
+        for i in bounds.start.div_euclid(size)..=(bounds.end - 1).div_euclid(size) {
+            let cells = (0..size).map(|o| tape.get(i * size + o).unwrap_or(&self.turing.default).clone()).collect();
+            blocks.push((i, self.intern(cells)));
+        }
+
+        let mut run = Run {
+            left: Vec::new(),
+            right: Vec::new(),
+            block: 0,
+            index,
+            offset: head.rem_euclid(size) as usize,
+            mem,
+            steps: 0,
+            lo: bounds.start,
+            hi: bounds.end - 1,
+        };
+        for &(i, block) in &blocks {
+            if i < index {
+                push(&mut run.left, block, 1);
+            } else if i == index {
+                run.block = block;
+            }
+        }
+        for &(i, block) in blocks.iter().rev() {
+            if i > index {
+                push(&mut run.right, block, 1);
+            }
+        }
+
+        self.drive(&mut run, fuel);
+        self.halt(run)
+    }
+
+    fn intern(&mut self, cells: Vec<Sym>) -> u32 {
+        if let Some(&id) = self.block_ids.get(&cells) {
+            return id;
+        }
+        let id = self.blocks.len() as u32;
+        self.block_ids.insert(cells.clone(), id);
+        self.blocks.push(cells);
+        id
+    }
+
+    /// Takes macro steps until the machine stops inside a block.
+    fn drive(&mut self, run: &mut Run<Mem>, fuel: Fuel) {
+        let blank = self.intern(vec![self.turing.default.clone(); self.size]);
+        let size = self.size as isize;
+        loop {
+            let key = (run.block, run.mem.clone(), run.offset);
+            let step = match self.macros.get(&key) {
+                Some(step) if !fuel.exhausted(&self.after(run, step)) => step.clone(),
+                _ => {
+                    let (step, complete) = self.simulate(run, fuel);
+                    if complete {
+                        self.macros.insert(key, step.clone());
+                    }
+                    step
+                }
+            };
+
+            let start = run.index * size;
+            run.steps += step.steps;
+            run.lo = run.lo.min(start + step.lo);
+            run.hi = run.hi.max(start + step.hi);
+            run.mem = step.mem;
+            match step.exit {
+                Exit::Inside(offset) => {
+                    run.block = step.block;
+                    run.offset = offset;
+                    return;
+                }
+                Exit::Right => {
+                    push(&mut run.left, step.block, 1);
+                    self.cross(run, fuel, Exit::Right);
+                    run.block = pop(&mut run.right, blank);
+                    run.index += 1;
+                    run.offset = 0;
+                }
+                Exit::Left => {
+                    push(&mut run.right, step.block, 1);
+                    self.cross(run, fuel, Exit::Left);
+                    run.block = pop(&mut run.left, blank);
+                    run.index -= 1;
+                    run.offset = self.size - 1;
+                }
+            }
+        }
+    }
+
+    /// Crosses the run of blocks the head has just stepped onto in one go, if the macro step for
+    /// the first of them keeps the state and carries on in the same direction.
+    fn cross(&mut self, run: &mut Run<Mem>, fuel: Fuel, exit: Exit) {
+        let size = self.size as isize;
+        let (stack, offset, sign) = match exit {
+            Exit::Right => (&run.right, 0, 1),
+            _ => (&run.left, self.size - 1, -1),
+        };
+        let &(block, count) = match stack.last() {
+            Some(top) => top,
+            None => return,
+        };
+        let step = match self.macros.get(&(block, run.mem.clone(), offset)) {
+            Some(step) if step.exit == exit && step.mem == run.mem => step,
+            _ => return,
+        };
+
+        let first = (run.index + sign) * size;
+        let last = (run.index + sign * count as isize) * size;
+        let (near, far) = if sign > 0 { (first, last) } else { (last, first) };
+        let stats = Stats {
+            steps: run.steps + count * step.steps,
+            cells: ((run.hi.max(far + step.hi)) - run.lo.min(near + step.lo) + 1) as usize,
+        };
+        if fuel.exhausted(&stats) {
+            return;
+        }
+
+        run.steps = stats.steps;
+        run.lo = run.lo.min(near + step.lo);
+        run.hi = run.hi.max(far + step.hi);
+        run.index += sign * count as isize;
+        let crossed = step.block;
+        if sign > 0 {
+            run.right.pop();
+            push(&mut run.left, crossed, count);
+        } else {
+            run.left.pop();
+            push(&mut run.right, crossed, count);
+        }
+    }
+
+    /// The stats after taking `step` from the block under the head.
+    fn after(&self, run: &Run<Mem>, step: &Macro<Mem>) -> Stats {
+        let start = run.index * self.size as isize;
+        Stats {
+            steps: run.steps + step.steps,
+            cells: (run.hi.max(start + step.hi) - run.lo.min(start + step.lo) + 1) as usize,
+        }
+    }
+
+    /// Runs the machine step by step from the head until it leaves the block or stops, the way
+    /// the plain engine would. Returns whether it got there without running out of `fuel`.
+    fn simulate(&mut self, run: &Run<Mem>, fuel: Fuel) -> (Macro<Mem>, bool) {
+        let size = self.size as isize;
+        let start = run.index * size;
+        let mut cells = self.blocks[run.block as usize].clone();
+        let mut mem = run.mem.clone();
+        let mut pos = run.offset as isize;
+        let (mut lo, mut hi, mut steps) = (pos, pos, 0);
+        let (exit, complete) = loop {
+            if self.turing.accepting.contains(&mem) || self.turing.rejecting.contains(&mem) {
+                break (Exit::Inside(pos as usize), true);
+            }
+            let action = self.turing.map.get(&(cells[pos as usize].clone(), mem.clone()));
+            let stats = Stats {
+                steps: run.steps + steps,
+                cells: (run.hi.max(start + hi) - run.lo.min(start + lo) + 1) as usize,
+            };
+            if fuel.exhausted(&stats) {
+                break (Exit::Inside(pos as usize), false);
+            }
+            let (dir, sym, next) = match action {
+                Some(action) => action,
+                None => break (Exit::Inside(pos as usize), true),
+            };
+
+            cells[pos as usize] = sym.clone();
+            mem = next.clone();
+            steps += 1;
+            pos += match dir {
+                Dir::Left => -1,
+                Dir::Stay => 0,
+                Dir::Right => 1,
+            };
+            lo = lo.min(pos);
+            hi = hi.max(pos);
+            if pos < 0 {
+                break (Exit::Left, true);
+            } else if pos == size {
+                break (Exit::Right, true);
+            }
+        };
+        (Macro { block: self.intern(cells), mem, exit, steps, lo, hi }, complete)
+    }
+
+    /// Spells the runs of blocks out into cells, cut down to the cells the plain engine would have
+    /// allocated, and tells from the state and the symbol under the head why the run ended.
+    fn halt(&self, run: Run<Mem>) -> Halt<Tape<Sym>, Mem> {
+        let size = self.size as isize;
+        let stats = run.stats();
+        let left: usize = run.left.iter().map(|&(_, n)| n).sum();
+        let first = (run.index - left as isize) * size;
+        let current = (run.block, 1);
+        let blocks = run.left.iter()
+            .chain(Some(&current))
+            .chain(run.right.iter().rev())
+            .flat_map(|&(block, n)| std::iter::repeat_n(block, n));
+        let mut cells: VecDeque<Sym> = blocks
+            .flat_map(|block| self.blocks[block as usize].iter().cloned())
+            .skip((run.lo - first).max(0) as usize)
+            .take((run.hi - run.lo + 1) as usize)
+            .collect();
+        for _ in run.lo..first {
+            cells.push_front(self.turing.default.clone());
+        }
+        cells.resize((run.hi - run.lo + 1) as usize, self.turing.default.clone());
+
+        let head = run.index * size + run.offset as isize;
+        let pos = (head - run.lo) as usize;
+        let sym = cells[pos].clone();
+        let space = Tape::from_parts(cells, pos, -run.lo);
+        let mem = run.mem;
+        if self.turing.accepting.contains(&mem) {
+            Halt::Accepted { space, mem, stats }
+        } else if self.turing.rejecting.contains(&mem) {
+            Halt::Rejected { space, mem, stats }
+        } else if self.turing.map.contains_key(&(sym.clone(), mem.clone())) {
+            Halt::OutOfFuel { space, mem, stats }
+        } else {
+            Halt::Stuck { space, mem, sym, stats }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ Fuel, Tape, turing, Dir::* };
+    use crate::fixtures::{ assert_agree, beaver, small_machines };
+
+    #[test]
+    fn agrees_with_plain_engine_on_small_machines() {
+        for t in small_machines(100) {
+            for size in 1..=4 {
+                let mut blocks = t.blocks(size);
+                for fuel in [Fuel::steps(30), Fuel::steps(100), Fuel::steps(100).cells(5)] {
+                    let plain = t.run_with_fuel(Tape::new(0, None), fuel);
+                    assert_agree(&plain, &blocks.run(Tape::new(0, None), fuel));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn agrees_with_plain_engine_on_input() {
+        let t = turing!(0u8;'A';'H';
+            ('A') { 0 => (Right, 1, 'B') 1 => (Left , 1, 'B') 2 => (Left, 0, 'A') },
+            ('B') { 0 => (Left , 1, 'A') 1 => (Right, 0, 'C') 2 => (Right, 1, 'B') },
+            ('C') { 0 => (Right, 1, 'H') 1 => (Left , 1, 'C') },
+        );
+        for head in -4..8 {
+            for size in 1..=4 {
+                let tape = Tape::with_head(0, head, vec![2, 1, 2, 2, 0, 1]);
+                for fuel in [Fuel::steps(1000), Fuel::steps(7), Fuel::steps(1000).cells(9)] {
+                    let plain = t.run_with_fuel(tape.clone(), fuel);
+                    assert_agree(&plain, &t.blocks(size).run(tape.clone(), fuel));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn crosses_runs_of_blocks() {
+        let t = beaver();
+        let plain = t.run(Tape::new(0, None));
+        assert_eq!(plain.stats().steps, 107);
+        for size in 1..=6 {
+            assert_agree(&plain, &t.blocks(size).run(Tape::new(0, None), Fuel::unlimited()));
+        }
+    }
+}
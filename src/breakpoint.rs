@@ -0,0 +1,195 @@
+use std::hash::Hash;
+
+use crate::{ Execution, Fuel, Rewind, Stats, Step, Tapes };
+
+/// A condition to pause a run on, checked after each step. Tapes are numbered from 0, so a
+/// single `Tape` is tape 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint<Sym, Mem> {
+    /// The machine enters `Mem` from another state.
+    State(Mem),
+    /// The machine takes the transition for reading `Sym` in `Mem`.
+    Transition(Mem, Sym),
+    /// The symbol in the cell `cell` of tape `tape` changes.
+    Cell { tape: usize, cell: isize },
+    /// The head of tape `tape` moves onto the cell `cell`.
+    Head { tape: usize, cell: isize },
+    /// The machine has taken this many steps.
+    Steps(usize),
+}
+
+impl<Sym: PartialEq, Mem: PartialEq> Breakpoint<Sym, Mem> {
+    /// Whether the breakpoint is hit by `step`, taken with the heads at `heads` and leading to
+    /// `space` after `steps` steps.
+    pub fn hit<S>(&self, step: &Step<S, Mem>, heads: &[isize], space: &S, steps: usize) -> bool
+        where S: Tapes<Sym = Sym>, S::Cell: PartialEq {
+        match self {
+            Breakpoint::State(mem) => step.to == *mem && step.from != *mem,
+            Breakpoint::Transition(mem, sym) => step.from == *mem && step.read == *sym,
+            Breakpoint::Cell { tape, cell } => heads.get(*tape) == Some(cell)
+                && S::syms(&step.read).get(*tape) != S::syms(&step.write).get(*tape),
+            Breakpoint::Head { tape, cell } => heads.get(*tape) != Some(cell)
+                && space.tapes().get(*tape).map(|t| t.head()) == Some(*cell),
+            Breakpoint::Steps(n) => steps == *n,
+        }
+    }
+}
+
+fn heads<S: Tapes>(space: &S) -> Vec<isize> {
+    space.tapes().iter().map(|tape| tape.head()).collect()
+}
+
+fn first_hit<S, Mem>(breakpoints: &[Breakpoint<S::Sym, Mem>], step: &Step<S, Mem>, heads: &[isize], space: &S, steps: usize) -> Option<usize>
+    where S: Tapes, S::Cell: PartialEq, Mem: PartialEq {
+    breakpoints.iter().position(|b| b.hit(step, heads, space, steps))
+}
+
+/// A run that `run_to` can drive.
+trait Run<S: Tapes, Mem> {
+    fn stats(&self) -> Stats;
+    fn space(&self) -> &S;
+    fn step(&mut self) -> Option<Step<S, Mem>>;
+}
+
+impl<'a, S, Mem> Run<S, Mem> for Execution<'a, S, Mem>
+    where S: Tapes, S::Sym: Hash, Mem: Clone + Eq + Hash {
+    fn stats(&self) -> Stats {
+        Execution::stats(self)
+    }
+
+    fn space(&self) -> &S {
+        Execution::space(self)
+    }
+
+    fn step(&mut self) -> Option<Step<S, Mem>> {
+        Execution::step(self)
+    }
+}
+
+impl<'a, S, Mem> Run<S, Mem> for Rewind<'a, S, Mem>
+    where S: Tapes + Clone, S::Sym: Hash, S::Cell: Clone, Mem: Clone + Eq + Hash {
+    fn stats(&self) -> Stats {
+        Rewind::stats(self)
+    }
+
+    fn space(&self) -> &S {
+        Rewind::space(self)
+    }
+
+    fn step(&mut self) -> Option<Step<S, Mem>> {
+        Rewind::step(self)
+    }
+}
+
+fn run_to<S, Mem, R>(run: &mut R, breakpoints: &[Breakpoint<S::Sym, Mem>], fuel: Fuel) -> Option<usize>
+    where S: Tapes, S::Cell: PartialEq, Mem: PartialEq, R: Run<S, Mem> {
+    while !fuel.exhausted(&run.stats()) {
+        let heads = heads(run.space());
+        let step = run.step()?;
+        if let Some(i) = first_hit(breakpoints, &step, &heads, run.space(), run.stats().steps) {
+            return Some(i);
+        }
+    }
+    None
+}
+
+impl<'a, S, Mem> Execution<'a, S, Mem>
+    where S: Tapes, S::Sym: Hash, S::Cell: PartialEq, Mem: Clone + Eq + Hash {
+    /// Steps until one of `breakpoints` is hit and returns its index, or `None` if the machine
+    /// halts or runs out of `fuel` first.
+    pub fn run_to(&mut self, breakpoints: &[Breakpoint<S::Sym, Mem>], fuel: Fuel) -> Option<usize> {
+        run_to(self, breakpoints, fuel)
+    }
+}
+
+impl<'a, S, Mem> Rewind<'a, S, Mem>
+    where S: Tapes + Clone, S::Sym: Hash, S::Cell: Clone + PartialEq, Mem: Clone + Eq + Hash {
+    /// Steps until one of `breakpoints` is hit and returns its index, or `None` if the machine
+    /// halts or runs out of `fuel` first.
+    pub fn run_to(&mut self, breakpoints: &[Breakpoint<S::Sym, Mem>], fuel: Fuel) -> Option<usize> {
+        run_to(self, breakpoints, fuel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Breakpoint;
+    use crate::{ Fuel, Rewind, Tape, Tapes, Turing, turing, Dir::* };
+
+    /// The index of the first of `breakpoints` hit and the steps taken then, checking that
+    /// stepping with and without rewinding agree.
+    fn hit<S>(t: &Turing<S, char>, space: S, breakpoints: &[Breakpoint<S::Sym, char>]) -> Option<(usize, usize)>
+        where S: Tapes + Clone, S::Sym: std::hash::Hash, S::Cell: Clone + PartialEq {
+        let mut exec = t.start(space.clone());
+        let found = exec.run_to(breakpoints, Fuel::steps(100)).map(|i| (i, exec.stats().steps));
+        let mut rewind = Rewind::with_snapshots(t.start(space), 2, 2);
+        assert_eq!(rewind.run_to(breakpoints, Fuel::steps(100)).map(|i| (i, rewind.stats().steps)), found);
+        found
+    }
+
+    /// Walks right over the input, marks the first blank and walks back, replacing each `1`.
+    fn walker() -> Turing<Tape<char>, char> {
+        turing!('_';'a';'h';
+            ('a') { '1' => (Right, '1', 'a') '_' => (Left , 'x', 'b') },
+            ('b') { '1' => (Left , '2', 'b') '_' => (Right, '_', 'h') },
+        )
+    }
+
+    fn input() -> Tape<char> {
+        Tape::new('_', "111".chars())
+    }
+
+    #[test]
+    fn state_fires_on_entering_from_another_state() {
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::State('a')]), None);
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::State('a'), Breakpoint::State('b')]), Some((1, 4)));
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::State('h')]), Some((0, 8)));
+    }
+
+    #[test]
+    fn transition_fires_on_taking_it() {
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::Transition('a', '1')]), Some((0, 1)));
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::Transition('b', '1')]), Some((0, 5)));
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::Transition('b', 'x')]), None);
+    }
+
+    #[test]
+    fn cell_fires_when_the_symbol_changes() {
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::Cell { tape: 0, cell: 0 }]), Some((0, 7)));
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::Cell { tape: 0, cell: 3 }]), Some((0, 4)));
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::Cell { tape: 0, cell: -1 }]), None);
+    }
+
+    #[test]
+    fn head_fires_when_the_head_moves_onto_the_cell() {
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::Head { tape: 0, cell: 3 }]), Some((0, 3)));
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::Head { tape: 0, cell: 0 }]), Some((0, 6)));
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::Head { tape: 0, cell: 5 }]), None);
+    }
+
+    #[test]
+    fn steps_fires_after_that_many_steps() {
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::Steps(5)]), Some((0, 5)));
+        assert_eq!(hit(&walker(), input(), &[Breakpoint::Steps(9)]), None);
+    }
+
+    #[test]
+    fn cell_and_head_pick_out_one_tape() {
+        // Copies a mark onto the second tape once, then walks it right when the first ends.
+        let t = turing!(['_', '_'];'a';'h';
+            ('a') {
+                ['1', '_'] => ([Right, Stay ], ['1', 'y'], 'a')
+                ['1', 'y'] => ([Right, Stay ], ['1', 'y'], 'a')
+                ['_', 'y'] => ([Stay , Right], ['_', 'y'], 'b')
+            },
+            ('b') { ['_', '_'] => ([Stay , Right], ['_', '_'], 'h') },
+        );
+        let space = || [Tape::new('_', "11".chars()), Tape::new('_', None)];
+        assert_eq!(hit(&t, space(), &[Breakpoint::Cell { tape: 1, cell: 0 }]), Some((0, 1)));
+        assert_eq!(hit(&t, space(), &[Breakpoint::Cell { tape: 0, cell: 0 }]), None);
+        assert_eq!(hit(&t, space(), &[Breakpoint::Head { tape: 1, cell: 1 }]), Some((0, 3)));
+        assert_eq!(hit(&t, space(), &[Breakpoint::Head { tape: 0, cell: 1 }]), Some((0, 1)));
+        assert_eq!(hit(&t, space(), &[Breakpoint::Head { tape: 2, cell: 0 }]), None);
+        assert_eq!(hit(&t, space(), &[Breakpoint::Transition('b', ['_', '_'])]), Some((0, 4)));
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::{ HashMap, VecDeque };
+use std::hash::Hash;
+
+use crate::{ Dir, Fuel, Halt, Stats, Tape, Turing };
+
+const NONE: u32 = u32::MAX;
+
+#[derive(Clone, Copy)]
+struct Action {
+    write: u32,
+    dir: Dir,
+    next: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Final {
+    No,
+    Accept,
+    Reject,
+}
+
+/// A single-tape machine with its states and symbols numbered, so a step is an array lookup.
+/// It runs exactly like the `Turing` it was compiled from.
+pub struct Compiled<Sym, Mem> {
+    states: Vec<Mem>,
+    state_ids: HashMap<Mem, u32>,
+    symbols: Vec<Sym>,
+    symbol_ids: HashMap<Sym, u32>,
+    finals: Vec<Final>,
+    table: Vec<Action>,
+    blank: u32,
+    initial: u32,
+}
+
+impl<Sym, Mem> Turing<Tape<Sym>, Mem>
+    where Sym: Clone + Eq + Hash, Mem: Clone + Eq + Hash {
+    pub fn compile(&self) -> Compiled<Sym, Mem> {
+        let mut states = Vec::new();
+        let mut state_ids = HashMap::new();
+        let mut symbols = Vec::new();
+        let mut symbol_ids = HashMap::new();
+
+        let blank = intern(&mut symbols, &mut symbol_ids, &self.default);
+        let initial = intern(&mut states, &mut state_ids, &self.initial);
+        for mem in self.accepting.iter().chain(&self.rejecting) {
+            intern(&mut states, &mut state_ids, mem);
+        }
+        for ((sym, mem), (_, sym_new, mem_new)) in &self.map {
+            intern(&mut symbols, &mut symbol_ids, sym);
+            intern(&mut symbols, &mut symbol_ids, sym_new);
+            intern(&mut states, &mut state_ids, mem);
+            intern(&mut states, &mut state_ids, mem_new);
+        }
+
+        let finals = states.iter()
+            .map(|mem| if self.accepting.contains(mem) {
+                Final::Accept
+            } else if self.rejecting.contains(mem) {
+                Final::Reject
+            } else {
+                Final::No
+            })
+            .collect();
+
+        let mut table = vec![Action { write: 0, dir: Dir::Stay, next: NONE }; states.len() * symbols.len()];
+        for ((sym, mem), (dir, sym_new, mem_new)) in &self.map {
+            table[state_ids[mem] as usize * symbols.len() + symbol_ids[sym] as usize] = Action {
+                write: symbol_ids[sym_new],
+                dir: *dir,
+                next: state_ids[mem_new],
+            };
+        }
+
+        Compiled { states, state_ids, symbols, symbol_ids, finals, table, blank, initial }
+    }
+}
+
+fn intern<T: Clone + Eq + Hash>(values: &mut Vec<T>, ids: &mut HashMap<T, u32>, value: &T) -> u32 {
+    *ids.entry(value.clone()).or_insert_with(|| {
+        values.push(value.clone());
+        values.len() as u32 - 1
+    })
+}
+
+impl<Sym, Mem> Compiled<Sym, Mem>
+    where Sym: Clone + Eq + Hash, Mem: Clone + Eq + Hash {
+    pub fn run(&self, tape: Tape<Sym>, fuel: Fuel) -> Halt<Tape<Sym>, Mem> {
+        self.resume(tape, self.states[self.initial as usize].clone(), fuel)
+    }
+
+    pub fn resume(&self, tape: Tape<Sym>, mem: Mem, fuel: Fuel) -> Halt<Tape<Sym>, Mem> {
+        let (tape, pos, origin) = tape.into_parts();
+        let size = tape.len();
+
+        // Symbols on the input that the machine never mentions have no transitions.
+        let mut extra = Vec::new();
+        let mut extra_ids = HashMap::new();
+        let cells = tape.into_iter()
+            .map(|sym| match self.symbol_ids.get(&sym) {
+                Some(&id) => id,
+                None => self.symbols.len() as u32 + intern(&mut extra, &mut extra_ids, &sym),
+            })
+            .collect();
+
+        let mut run = Run { cells, pos, lo: 0, hi: size - 1, base: 0, state: 0, steps: 0 };
+        let end = match self.state_ids.get(&mem) {
+            Some(&state) => {
+                run.state = state;
+                self.drive(&mut run, fuel)
+            }
+            None => End::Stuck,
+        };
+
+        let mem = if self.state_ids.contains_key(&mem) { self.states[run.state as usize].clone() } else { mem };
+        let stats = Stats { steps: run.steps, cells: run.hi - run.lo + 1 };
+        let sym = self.symbol(&extra, run.cells[run.pos]).clone();
+        let space = self.tape(&extra, run, origin);
+        match end {
+            End::Accepted => Halt::Accepted { space, mem, stats },
+            End::Rejected => Halt::Rejected { space, mem, stats },
+            End::Stuck => Halt::Stuck { space, mem, sym, stats },
+            End::OutOfFuel => Halt::OutOfFuel { space, mem, stats },
+        }
+    }
+
+    fn drive(&self, run: &mut Run, fuel: Fuel) -> End {
+        let width = self.symbols.len();
+        let none = Action { write: 0, dir: Dir::Stay, next: NONE };
+        loop {
+            let state = run.state as usize;
+            match self.finals[state] {
+                Final::Accept => return End::Accepted,
+                Final::Reject => return End::Rejected,
+                Final::No => (),
+            }
+
+            let sym = run.cells[run.pos] as usize;
+            let action = if sym < width { self.table[state * width + sym] } else { none };
+            if fuel.exhausted(&Stats { steps: run.steps, cells: run.hi - run.lo + 1 }) {
+                return if action.next == NONE { End::Stuck } else { End::OutOfFuel };
+            }
+            if action.next == NONE {
+                return End::Stuck;
+            }
+
+            run.cells[run.pos] = action.write;
+            run.state = action.next;
+            run.steps += 1;
+            match action.dir {
+                Dir::Left => {
+                    if run.pos == 0 {
+                        run.grow(self.blank);
+                    }
+                    run.pos -= 1;
+                    run.lo = run.lo.min(run.pos);
+                }
+                Dir::Stay => (),
+                Dir::Right => {
+                    run.pos += 1;
+                    if run.pos == run.cells.len() {
+                        let len = run.cells.len();
+                        run.cells.resize(2 * len, self.blank);
+                    }
+                    run.hi = run.hi.max(run.pos);
+                }
+            }
+        }
+    }
+
+    fn symbol<'a>(&'a self, extra: &'a [Sym], id: u32) -> &'a Sym {
+        let id = id as usize;
+        if id < self.symbols.len() {
+            &self.symbols[id]
+        } else {
+            &extra[id - self.symbols.len()]
+        }
+    }
+
+    /// Turns the cells back into the tape the plain engine would have built, which holds the
+    /// input and exactly the cells the head has visited.
+    fn tape(&self, extra: &[Sym], run: Run, origin: isize) -> Tape<Sym> {
+        let tape: VecDeque<Sym> = run.cells[run.lo..=run.hi].iter().map(|&id| self.symbol(extra, id).clone()).collect();
+        Tape::from_parts(tape, run.pos - run.lo, origin + (run.base - run.lo) as isize)
+    }
+}
+
+enum End {
+    Accepted,
+    Rejected,
+    Stuck,
+    OutOfFuel,
+}
+
+/// The state of a run. `lo` and `hi` bound the cells the plain engine would have allocated and
+/// `base` is where the input starts in `cells`.
+struct Run {
+    cells: Vec<u32>,
+    pos: usize,
+    lo: usize,
+    hi: usize,
+    base: usize,
+    state: u32,
+    steps: usize,
+}
+
+impl Run {
+    fn grow(&mut self, blank: u32) {
+        let grown = self.cells.len().max(16);
+        self.cells.splice(0..0, std::iter::repeat_n(blank, grown));
+        self.pos += grown;
+        self.lo += grown;
+        self.hi += grown;
+        self.base += grown;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ Fuel, Halt, Tape, Turing, turing, Dir::* };
+    use crate::fixtures::{ self, small_machines };
+
+    fn assert_agree<Mem: Clone + Eq + std::hash::Hash + std::fmt::Debug>(t: &Turing<Tape<u8>, Mem>, tape: Tape<u8>, mem: Mem, fuel: Fuel) {
+        let plain = t.resume(tape.clone(), mem.clone(), fuel);
+        fixtures::assert_agree(&plain, &t.compile().resume(tape, mem, fuel));
+    }
+
+    #[test]
+    fn agrees_with_plain_engine_under_fuel() {
+        for t in small_machines(60) {
+            for fuel in [Fuel::steps(20), Fuel::steps(60), Fuel::steps(60).cells(4), Fuel::unlimited().cells(6)] {
+                assert_agree(&t, Tape::new(0, None), 0, fuel.steps.map_or(Fuel::steps(200).cells(6), |_| fuel));
+            }
+        }
+    }
+
+    #[test]
+    fn agrees_with_plain_engine_on_unmentioned_symbols() {
+        let t = turing!(0u8;'A';'H';
+            ('A') { 0 => (Right, 1, 'H') 1 => (Right, 0, 'A') },
+        );
+        for head in -1..6 {
+            assert_agree(&t, Tape::with_head(0, head, vec![1, 7, 9, 1]), 'A', Fuel::unlimited());
+        }
+    }
+
+    #[test]
+    fn agrees_with_plain_engine_when_growing_left() {
+        // Walks off the left end of the input, so the cells have to grow to the left.
+        let t = turing!(0u8;'A';'H';
+            ('A') { 0 => (Left , 1, 'B') 1 => (Left , 1, 'A') },
+            ('B') { 0 => (Left , 1, 'C') 1 => (Left , 1, 'B') },
+            ('C') { 0 => (Right, 2, 'H') },
+        );
+        for len in [0, 1, 5, 40] {
+            for head in [-3, 0, 2] {
+                let tape = Tape::with_head(0, head, vec![1; len]);
+                assert_agree(&t, tape.clone(), 'A', Fuel::unlimited());
+                assert_agree(&t, tape.clone(), 'A', Fuel::steps(10));
+                assert_agree(&t, tape, 'A', Fuel::unlimited().cells(20));
+            }
+        }
+    }
+
+    #[test]
+    fn resumes_in_an_unknown_state() {
+        let t = turing!(0u8;'A';'H';
+            ('A') { 0 => (Right, 1, 'H') },
+        );
+        assert_agree(&t, Tape::new(0, vec![1, 0]), 'Z', Fuel::unlimited());
+        assert!(matches!(t.compile().resume(Tape::new(0, None), 'Z', Fuel::unlimited()), Halt::Stuck { mem: 'Z', sym: 0, .. }));
+    }
+}
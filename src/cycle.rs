@@ -0,0 +1,115 @@
+use std::hash::Hash;
+
+use crate::{ Execution, Fuel, Halt, Space, Turing };
+
+/// The result of running a machine while looking for a repeated configuration.
+pub enum Verdict<S: Space, Mem> {
+    /// The machine halted, or ran out of fuel before a configuration repeated.
+    Halted(Halt<S, Mem>),
+    /// The configuration after `start` steps, `space` and `mem`, comes back every `period`
+    /// steps, so the machine never halts. `space` is normalized.
+    Cycle { start: usize, period: usize, space: S, mem: Mem },
+    /// From step `start` on the machine repeats the same moves every `period` steps, each time
+    /// `shift` cells further along a blank tape, so it never halts. The steps `start` and
+    /// `start + period` witness this, and `space` and `mem` are the configuration at the second.
+    Translated { start: usize, period: usize, shift: isize, space: S, mem: Mem },
+}
+
+impl<S: Space, Mem> Verdict<S, Mem> {
+    /// Whether the machine was shown to never halt.
+    pub fn is_cycle(&self) -> bool {
+        !matches!(self, Verdict::Halted(_))
+    }
+}
+
+impl<S, Mem> Turing<S, Mem>
+    where S: Space + Clone + PartialEq, S::Sym: Hash, Mem: Clone + Eq + Hash {
+    /// Runs the machine like `run_with_fuel`, but stops once a configuration repeats, comparing
+    /// configurations with their spaces normalized.
+    ///
+    /// This uses Brent's algorithm, so it only holds two configurations at a time. Finding where
+    /// the cycle starts runs the machine again from `space`, which does not count towards `fuel`.
+    pub fn detect_cycle(&self, space: S, fuel: Fuel) -> Verdict<S, Mem> {
+        let mut hare = self.start(space.clone());
+        let mut tortoise = self.configuration(&hare);
+        let (mut power, mut period) = (1, 0);
+        loop {
+            if hare.is_halted() || fuel.exhausted(&hare.stats()) {
+                return Verdict::Halted(hare.into_halt());
+            }
+            hare.step();
+            period += 1;
+            if self.at(&hare, &tortoise) {
+                break;
+            }
+            if period == power {
+                tortoise = self.configuration(&hare);
+                power *= 2;
+                period = 0;
+            }
+        }
+
+        let mut tortoise = self.start(space.clone());
+        let mut hare = self.start(space);
+        for _ in 0..period {
+            hare.step();
+        }
+        while !self.at(&hare, &self.configuration(&tortoise)) {
+            tortoise.step();
+            hare.step();
+        }
+        let (space, mem) = self.configuration(&tortoise);
+        Verdict::Cycle { start: tortoise.stats().steps, period, space, mem }
+    }
+
+    fn configuration(&self, exec: &Execution<'_, S, Mem>) -> (S, Mem) {
+        let mut space = exec.space().clone();
+        space.normalize(&self.default);
+        (space, exec.state().clone())
+    }
+
+    fn at(&self, exec: &Execution<'_, S, Mem>, (space, mem): &(S, Mem)) -> bool {
+        exec.state() == mem && self.configuration(exec).0 == *space
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ Fuel, Halt, Tape, Verdict, turing, Dir::* };
+
+    #[test]
+    fn finds_a_cycle_from_the_start() {
+        let t = turing!('_';'a';'h';
+            ('a') { '_' => (Right, '_', 'c') },
+            ('c') { '_' => (Left , '_', 'a') },
+        );
+        match t.detect_cycle(Tape::new('_', None), Fuel::unlimited()) {
+            Verdict::Cycle { start, period, mem, .. } => assert_eq!((start, period, mem), (0, 2, 'a')),
+            _ => panic!("expected a cycle"),
+        }
+    }
+
+    #[test]
+    fn finds_where_a_cycle_starts() {
+        // Walks over the input before it starts bouncing between two cells.
+        let t = turing!('_';'a';'h';
+            ('a') { '1' => (Right, '1', 'a') '_' => (Right, '_', 'b') },
+            ('b') { '_' => (Right, '_', 'c') },
+            ('c') { '_' => (Left , '_', 'b') },
+        );
+        match t.detect_cycle(Tape::new('_', "111".chars()), Fuel::unlimited()) {
+            Verdict::Cycle { start, period, mem, .. } => assert_eq!((start, period, mem), (4, 2, 'b')),
+            _ => panic!("expected a cycle"),
+        }
+    }
+
+    #[test]
+    fn lets_halting_machines_halt() {
+        let t = turing!('_';'a';'h';
+            ('a') { '_' => (Right, '1', 'b') '1' => (Right, '1', 'h') },
+            ('b') { '_' => (Left , '1', 'a') },
+        );
+        assert!(matches!(t.detect_cycle(Tape::new('_', None), Fuel::unlimited()), Verdict::Halted(Halt::Accepted { .. })));
+        assert!(matches!(t.detect_cycle(Tape::new('_', None), Fuel::steps(1)), Verdict::Halted(Halt::OutOfFuel { .. })));
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::{ BTreeMap, HashMap, HashSet };
+use std::fmt::Write;
+use std::hash::Hash;
+
+use crate::{ Label, Space, Turing };
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl<S: Space, Mem> Turing<S, Mem>
+    where S::Sym: Label, S::Dir: Label, Mem: Label + Eq + Hash {
+    /// Renders the state diagram in the Graphviz DOT language.
+    ///
+    /// An arrow from a point marks the initial state, accepting states are drawn as double
+    /// circles and rejecting states as octagons. Transitions between the same two states share one edge, with a
+    /// `read → write, dir` line for each.
+    pub fn to_dot(&self) -> String {
+        let mut states: HashSet<&Mem> = HashSet::new();
+        states.insert(&self.initial);
+        states.extend(&self.accepting);
+        states.extend(&self.rejecting);
+        for ((_, mem), (_, _, mem_new)) in &self.map {
+            states.insert(mem);
+            states.insert(mem_new);
+        }
+
+        let mut states: Vec<(String, &Mem)> = states.into_iter().map(|mem| (mem.label(), mem)).collect();
+        states.sort_by(|a, b| a.0.cmp(&b.0));
+        let ids: HashMap<&Mem, usize> = states.iter().enumerate().map(|(i, (_, mem))| (*mem, i)).collect();
+
+        let mut edges: BTreeMap<(usize, usize), Vec<String>> = BTreeMap::new();
+        for ((sym, mem), (dir, sym_new, mem_new)) in &self.map {
+            let label = format!("{} → {}, {}", sym.label(), sym_new.label(), dir.label());
+            edges.entry((ids[mem], ids[mem_new])).or_default().push(label);
+        }
+
+        let mut dot = String::new();
+        dot.push_str("digraph turing {\n");
+        dot.push_str("    rankdir=LR;\n");
+        dot.push_str("    node [shape=circle];\n");
+        for (i, (label, mem)) in states.iter().enumerate() {
+            let shape = if self.accepting.contains(*mem) {
+                ", shape=doublecircle"
+            } else if self.rejecting.contains(*mem) {
+                ", shape=octagon"
+            } else {
+                ""
+            };
+            writeln!(dot, "    s{} [label=\"{}\"{}];", i, escape(label), shape).unwrap();
+        }
+        dot.push_str("    start [shape=point];\n");
+        writeln!(dot, "    start -> s{};", ids[&self.initial]).unwrap();
+        for ((from, to), mut labels) in edges {
+            labels.sort();
+            let label = labels.iter().map(|l| escape(l)).collect::<Vec<_>>().join("\\n");
+            writeln!(dot, "    s{} -> s{} [label=\"{}\"];", from, to, label).unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ Tape, Turing, turing, Dir::* };
+
+    #[test]
+    fn draws_states_and_merges_parallel_edges() {
+        let t: Turing<Tape<char>, &str> = turing!('_';"a";["h"];["r"];
+            ("a") { '_' => (Right, '1', "h") '1' => (Right, '_', "a") '0' => (Left, '0', "b") },
+            ("b") { '_' => (Stay, '_', "r") '1' => (Stay, '1', "r") },
+        );
+        assert_eq!(t.to_dot(), "\
+digraph turing {
+    rankdir=LR;
+    node [shape=circle];
+    s0 [label=\"a\"];
+    s1 [label=\"b\"];
+    s2 [label=\"h\", shape=doublecircle];
+    s3 [label=\"r\", shape=octagon];
+    start [shape=point];
+    start -> s0;
+    s0 -> s0 [label=\"1 → _, R\"];
+    s0 -> s1 [label=\"0 → 0, L\"];
+    s0 -> s2 [label=\"_ → 1, R\"];
+    s1 -> s3 [label=\"1 → 1, S\\n_ → _, S\"];
+}
+");
+    }
+
+    #[test]
+    fn marks_an_initial_state_that_also_halts() {
+        let t: Turing<Tape<char>, &str> = turing!('_';"S";["S"];[];
+            ("S") { '1' => (Right, '1', "S") },
+        );
+        let dot = t.to_dot();
+        assert!(dot.contains("s0 [label=\"S\", shape=doublecircle];"), "{}", dot);
+        assert!(dot.contains("start -> s0;"), "{}", dot);
+    }
+
+    #[test]
+    fn labels_symbols_of_several_tapes() {
+        let t: Turing<[Tape<char>; 2], &str> = turing!(['_', '_'];"a";"h";
+            ("a") { ['1', '_'] => ([Right, Stay], ['1', 'x'], "h") },
+        );
+        assert!(t.to_dot().contains("s0 -> s1 [label=\"1_ → 1x, RS\"];"), "{}", t.to_dot());
+    }
+}
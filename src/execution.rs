@@ -0,0 +1,139 @@
+use std::hash::Hash;
+
+use crate::{ Fuel, Halt, Space, Stats, Turing };
+
+/// A transition taken by an `Execution`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step<S: Space, Mem> {
+    pub from: Mem,
+    pub read: S::Sym,
+    pub dir: S::Dir,
+    pub write: S::Sym,
+    pub to: Mem,
+}
+
+/// A machine paused between two steps, holding its current space and state.
+pub struct Execution<'a, S: Space, Mem> {
+    turing: &'a Turing<S, Mem>,
+    space: S,
+    mem: Mem,
+    stats: Stats,
+}
+
+impl<'a, S: Space, Mem> Execution<'a, S, Mem>
+    where S::Sym: Hash, Mem: Clone + Eq + Hash {
+    pub fn new(turing: &'a Turing<S, Mem>, space: S) -> Self {
+        Execution::resume(turing, space, turing.initial.clone())
+    }
+
+    pub fn resume(turing: &'a Turing<S, Mem>, space: S, mem: Mem) -> Self {
+        let stats = Stats { steps: 0, cells: space.size() };
+        Execution { turing, space, mem, stats }
+    }
+
+    pub fn turing(&self) -> &'a Turing<S, Mem> {
+        self.turing
+    }
+
+    pub fn state(&self) -> &Mem {
+        &self.mem
+    }
+
+    pub fn space(&self) -> &S {
+        &self.space
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        self.turing.is_accepting(&self.mem)
+    }
+
+    pub fn is_rejected(&self) -> bool {
+        self.turing.is_rejecting(&self.mem)
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.pending().is_none()
+    }
+
+    /// The transition the next call to `step` will take.
+    pub fn pending(&self) -> Option<&'a (S::Dir, S::Sym, Mem)> {
+        if self.is_accepted() || self.is_rejected() {
+            return None;
+        }
+        self.turing.map.get(&(self.space.read(), self.mem.clone()))
+    }
+
+    pub fn step(&mut self) -> Option<Step<S, Mem>> {
+        if self.is_accepted() || self.is_rejected() {
+            return None;
+        }
+
+        let read = self.space.read();
+        let (dir, write, to) = self.turing.map.get(&(read.clone(), self.mem.clone()))?;
+        self.space.write(write.clone());
+        self.space.mov(dir, &self.turing.default);
+        self.stats.steps += 1;
+        self.stats.cells = self.space.size();
+        let from = std::mem::replace(&mut self.mem, to.clone());
+        Some(Step { from, read, dir: dir.clone(), write: write.clone(), to: to.clone() })
+    }
+
+    pub(crate) fn restore(&mut self, space: S, mem: Mem, stats: Stats) {
+        self.space = space;
+        self.mem = mem;
+        self.stats = stats;
+    }
+
+    pub(crate) fn parts_mut(&mut self) -> (&mut S, &mut Mem, &mut Stats) {
+        (&mut self.space, &mut self.mem, &mut self.stats)
+    }
+
+    /// Runs until the machine halts or `fuel`, counted from the start of this execution, runs out.
+    pub fn run(self, fuel: Fuel) -> Halt<S, Mem> {
+        self.debug(fuel, |_, _| ())
+    }
+
+    pub fn debug<F: Fn(&S, &Mem)>(mut self, fuel: Fuel, peek: F) -> Halt<S, Mem> {
+        loop {
+            if self.is_accepted() || self.is_rejected() || fuel.exhausted(&self.stats) {
+                return self.into_halt();
+            }
+
+            peek(&self.space, &self.mem);
+            if self.step().is_none() {
+                return self.into_halt();
+            }
+        }
+    }
+
+    /// Ends the execution where it is. A machine that can still move is reported as out of fuel.
+    pub fn into_halt(self) -> Halt<S, Mem> {
+        let Execution { space, mem, stats, .. } = self;
+        if self.turing.is_accepting(&mem) {
+            return Halt::Accepted { space, mem, stats };
+        }
+        if self.turing.is_rejecting(&mem) {
+            return Halt::Rejected { space, mem, stats };
+        }
+
+        let sym = space.read();
+        if self.turing.map.contains_key(&(sym.clone(), mem.clone())) {
+            Halt::OutOfFuel { space, mem, stats }
+        } else {
+            Halt::Stuck { space, mem, sym, stats }
+        }
+    }
+}
+
+impl<'a, S: Space, Mem> Iterator for Execution<'a, S, Mem>
+    where S::Sym: Hash, Mem: Clone + Eq + Hash {
+    type Item = Step<S, Mem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step()
+    }
+}
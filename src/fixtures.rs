@@ -0,0 +1,38 @@
+//! Machines and checks shared by the tests of the engines.
+
+use std::fmt::Debug;
+
+use crate::{ Halt, Tape, Turing, turing, Dir::* };
+use crate::beaver::Enumeration;
+
+/// The four state busy beaver champion, which halts after 107 steps.
+pub(crate) fn beaver() -> Turing<Tape<u8>, char> {
+    turing!(0u8;'A';'H';
+        ('A') { 0 => (Right, 1, 'B') 1 => (Left , 1, 'B') },
+        ('B') { 0 => (Left , 1, 'A') 1 => (Left , 0, 'C') },
+        ('C') { 0 => (Right, 1, 'H') 1 => (Left , 1, 'D') },
+        ('D') { 0 => (Right, 1, 'D') 1 => (Right, 0, 'A') },
+    )
+}
+
+/// Every machine with two or three states and symbols that the enumeration reaches when it runs
+/// candidates for up to `limit` steps.
+pub(crate) fn small_machines(limit: usize) -> impl Iterator<Item = Turing<Tape<u8>, u8>> {
+    [(2, 2), (3, 2), (2, 3)].iter()
+        .flat_map(move |&(states, symbols)| Enumeration::new(states, symbols, limit))
+        .map(|candidate| candidate.machine)
+}
+
+/// Asserts that another engine stopped exactly where the plain engine did, down to the cells it
+/// allocated.
+pub(crate) fn assert_agree<Mem: PartialEq + Debug>(plain: &Halt<Tape<u8>, Mem>, other: &Halt<Tape<u8>, Mem>) {
+    assert_eq!(std::mem::discriminant(plain), std::mem::discriminant(other));
+    if let (Halt::Stuck { sym: a, .. }, Halt::Stuck { sym: b, .. }) = (plain, other) {
+        assert_eq!(a, b);
+    }
+    assert_eq!(plain.space(), other.space());
+    assert_eq!(plain.space().head(), other.space().head());
+    assert_eq!(plain.space().bounds(), other.space().bounds());
+    assert_eq!(plain.mem(), other.mem());
+    assert_eq!(plain.stats(), other.stats());
+}
@@ -0,0 +1,87 @@
+use crate::Space;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Stats {
+    pub steps: usize,
+    pub cells: usize,
+}
+
+/// A budget for a run. `None` leaves the corresponding resource unbounded.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Fuel {
+    pub steps: Option<usize>,
+    pub cells: Option<usize>,
+}
+
+impl Fuel {
+    pub fn unlimited() -> Self {
+        Fuel::default()
+    }
+
+    pub fn steps(steps: usize) -> Self {
+        Fuel { steps: Some(steps), cells: None }
+    }
+
+    pub fn cells(self, cells: usize) -> Self {
+        Fuel { cells: Some(cells), ..self }
+    }
+
+    pub fn exhausted(&self, stats: &Stats) -> bool {
+        self.steps.is_some_and(|steps| stats.steps >= steps)
+            || self.cells.is_some_and(|cells| stats.cells > cells)
+    }
+}
+
+/// Why a machine stopped, together with the configuration it stopped in.
+#[derive(Debug)]
+pub enum Halt<S: Space, Mem> {
+    /// The machine entered the accepting state `mem`.
+    Accepted { space: S, mem: Mem, stats: Stats },
+    /// The machine entered the rejecting state `mem`.
+    Rejected { space: S, mem: Mem, stats: Stats },
+    /// There is no transition for `mem` reading `sym`.
+    Stuck { space: S, mem: Mem, sym: S::Sym, stats: Stats },
+    /// The run used up its `Fuel`. Pass `space` and `mem` to `Turing::resume` to continue.
+    OutOfFuel { space: S, mem: Mem, stats: Stats },
+}
+
+impl<S: Space, Mem> Halt<S, Mem> {
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, Halt::Accepted { .. })
+    }
+
+    pub fn is_rejected(&self) -> bool {
+        matches!(self, Halt::Rejected { .. })
+    }
+
+    pub fn space(&self) -> &S {
+        match self {
+            Halt::Accepted { space, .. } | Halt::Rejected { space, .. } | Halt::Stuck { space, .. } | Halt::OutOfFuel { space, .. } => space,
+        }
+    }
+
+    pub fn mem(&self) -> &Mem {
+        match self {
+            Halt::Accepted { mem, .. } | Halt::Rejected { mem, .. } | Halt::Stuck { mem, .. } | Halt::OutOfFuel { mem, .. } => mem,
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        match self {
+            Halt::Accepted { stats, .. } | Halt::Rejected { stats, .. } | Halt::Stuck { stats, .. } | Halt::OutOfFuel { stats, .. } => *stats,
+        }
+    }
+
+    pub fn into_space(self) -> S {
+        match self {
+            Halt::Accepted { space, .. } | Halt::Rejected { space, .. } | Halt::Stuck { space, .. } | Halt::OutOfFuel { space, .. } => space,
+        }
+    }
+
+    pub fn accepted(self) -> Option<S> {
+        match self {
+            Halt::Accepted { space, .. } => Some(space),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::Dir;
+
+/// A short human readable name for a symbol, direction or state.
+///
+/// Symbols and directions of multi-tape machines are labelled by their components, written
+/// next to each other when each is a single character and separated by commas otherwise, so
+/// `['#', '_', '1']` is labelled `#_1` and `[Left, Stay]` is labelled `LS`.
+pub trait Label {
+    fn label(&self) -> String;
+}
+
+macro_rules! impl_display_label {
+    ($($T:ty),+) => {
+        $(impl Label for $T {
+            fn label(&self) -> String {
+                self.to_string()
+            }
+        })+
+    };
+}
+
+impl_display_label!(char, bool, String, str, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Label for Dir {
+    fn label(&self) -> String {
+        match self {
+            Dir::Left => "L",
+            Dir::Stay => "S",
+            Dir::Right => "R",
+        }.to_owned()
+    }
+}
+
+impl<T: Label + ?Sized> Label for &T {
+    fn label(&self) -> String {
+        (**self).label()
+    }
+}
+
+fn join(labels: Vec<String>) -> String {
+    if labels.iter().all(|l| l.chars().count() == 1) {
+        labels.concat()
+    } else {
+        labels.join(",")
+    }
+}
+
+impl<T: Label, const N: usize> Label for [T; N] {
+    fn label(&self) -> String {
+        join(self.iter().map(Label::label).collect())
+    }
+}
+
+impl<T: Label> Label for Vec<T> {
+    fn label(&self) -> String {
+        join(self.iter().map(Label::label).collect())
+    }
+}
+
+macro_rules! impl_tuple_label {
+    ($($n:tt : $T:ident),+) => {
+        impl<$($T: Label),+> Label for ($($T),+) {
+            fn label(&self) -> String {
+                join(vec![$(self.$n.label()),+])
+            }
+        }
+    };
+}
+
+impl_tuple_label!(0:T0,1:T1);
+impl_tuple_label!(0:T0,1:T1,2:T2);
+impl_tuple_label!(0:T0,1:T1,2:T2,3:T3);
+impl_tuple_label!(0:T0,1:T1,2:T2,3:T3,4:T4);
+impl_tuple_label!(0:T0,1:T1,2:T2,3:T3,4:T4,5:T5);
+impl_tuple_label!(0:T0,1:T1,2:T2,3:T3,4:T4,5:T5,6:T6);
+impl_tuple_label!(0:T0,1:T1,2:T2,3:T3,4:T4,5:T5,6:T6,7:T7);
+impl_tuple_label!(0:T0,1:T1,2:T2,3:T3,4:T4,5:T5,6:T6,7:T7,8:T8);
+impl_tuple_label!(0:T0,1:T1,2:T2,3:T3,4:T4,5:T5,6:T6,7:T7,8:T8,9:T9);
@@ -1,11 +1,44 @@
-use std::collections::HashMap;
+use std::collections::{ HashMap, HashSet };
 use std::hash::Hash;
 
+pub mod backward;
+pub mod beaver;
+pub mod block;
+pub mod breakpoint;
+pub mod compiled;
+pub mod cycle;
+pub mod dot;
+pub mod execution;
+#[cfg(test)]
+mod fixtures;
+pub mod halt;
+pub mod label;
+pub mod lint;
+pub mod nondeterministic;
+pub mod parse;
+pub mod rewind;
 pub mod space;
+pub mod svg;
 pub mod tape;
+pub mod trace;
+pub mod translated;
 
+pub use beaver::{ Candidate, Enumeration, Outcome };
+pub use block::Blocks;
+pub use breakpoint::Breakpoint;
+pub use compiled::Compiled;
+pub use cycle::Verdict;
+pub use execution::{ Execution, Step };
+pub use halt::{ Fuel, Halt, Stats };
+pub use label::Label;
+pub use lint::Lint;
+pub use nondeterministic::{ NondeterministicTuring, Search };
+pub use parse::{ ParseError, ParseErrorKind };
+pub use rewind::Rewind;
 pub use space::Space;
-pub use tape::Tape;
+pub use svg::{ SpaceTime, SvgOptions };
+pub use tape::{ Tape, Tapes };
+pub use trace::{ Configuration, Record, Replay, Trace, TraceError, TraceErrorKind };
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Dir {
@@ -14,60 +47,97 @@ pub enum Dir {
     Right,
 }
 
+pub type Transitions<S, Mem> = HashMap<(<S as Space>::Sym, Mem), (<S as Space>::Dir, <S as Space>::Sym, Mem)>;
+
 pub struct Turing<S: Space, Mem> {
-    map: HashMap<(S::Sym, Mem), (S::Dir, S::Sym, Mem)>,
+    map: Transitions<S, Mem>,
     default: S::Sym,
     initial: Mem,
-    accepted: Mem,
+    accepting: HashSet<Mem>,
+    rejecting: HashSet<Mem>,
 }
 
 impl<S: Space, Mem> Turing<S, Mem> {
-    pub fn new(map: HashMap<(S::Sym, Mem), (S::Dir, S::Sym, Mem)>, default: S::Sym, initial: Mem, accepted: Mem) -> Self {
-        Turing { map, default, initial, accepted }
+    pub fn new<A, R>(map: Transitions<S, Mem>, default: S::Sym, initial: Mem, accepting: A, rejecting: R) -> Self
+        where Mem: Eq + Hash, A: IntoIterator<Item = Mem>, R: IntoIterator<Item = Mem> {
+        Turing {
+            map,
+            default,
+            initial,
+            accepting: accepting.into_iter().collect(),
+            rejecting: rejecting.into_iter().collect(),
+        }
+    }
+
+    pub fn blank(&self) -> &S::Sym {
+        &self.default
+    }
+
+    pub fn initial(&self) -> &Mem {
+        &self.initial
+    }
+
+    pub fn transitions(&self) -> &Transitions<S, Mem> {
+        &self.map
+    }
+
+    pub fn is_accepting(&self, mem: &Mem) -> bool
+        where Mem: Eq + Hash {
+        self.accepting.contains(mem)
     }
 
-    fn step(&self, mut space: S, mem: Mem) -> Option<(S, Mem)>
+    pub fn is_rejecting(&self, mem: &Mem) -> bool
+        where Mem: Eq + Hash {
+        self.rejecting.contains(mem)
+    }
+
+    pub fn start(&self, space: S) -> Execution<'_, S, Mem>
         where S::Sym: Hash, Mem: Clone + Eq + Hash {
-        let (dir, sym, mem) = self.map.get(&(space.read(), mem))?;
-        space.write(sym.clone());
-        space.mov(dir, &self.default);
-        Some((space, mem.clone()))
+        Execution::new(self, space)
     }
 
-    pub fn run(&self, mut space: S) -> Option<S>
+    pub fn run(&self, space: S) -> Halt<S, Mem>
         where S::Sym: Hash, Mem: Clone + Eq + Hash {
-        let mut mem = self.initial.clone();
-        while mem != self.accepted {
-            let state = self.step(space, mem)?;
-            space = state.0;
-            mem = state.1;
-        }
-        Some(space)
+        self.debug(space, |_, _| ())
     }
 
-    pub fn debug<F: Fn(&S, &Mem)>(&self, mut space: S, peek: F) -> Option<S>
+    pub fn run_with_limit(&self, space: S, max_steps: usize) -> Halt<S, Mem>
         where S::Sym: Hash, Mem: Clone + Eq + Hash {
-        let mut mem = self.initial.clone();
-        while mem != self.accepted {
-            peek(&space, &mem);
-            let state = self.step(space, mem)?;
-            space = state.0;
-            mem = state.1;
-        }
-        Some(space)
+        self.run_with_fuel(space, Fuel::steps(max_steps))
+    }
+
+    pub fn run_with_fuel(&self, space: S, fuel: Fuel) -> Halt<S, Mem>
+        where S::Sym: Hash, Mem: Clone + Eq + Hash {
+        self.resume(space, self.initial.clone(), fuel)
+    }
+
+    pub fn resume(&self, space: S, mem: Mem, fuel: Fuel) -> Halt<S, Mem>
+        where S::Sym: Hash, Mem: Clone + Eq + Hash {
+        Execution::resume(self, space, mem).run(fuel)
+    }
+
+    pub fn debug<F: Fn(&S, &Mem)>(&self, space: S, peek: F) -> Halt<S, Mem>
+        where S::Sym: Hash, Mem: Clone + Eq + Hash {
+        self.start(space).debug(Fuel::unlimited(), peek)
     }
 }
 
 #[macro_export]
 macro_rules! turing {
-    ($default:expr ; $initial:expr ; $accept:expr ; $(($mem:expr) { $($rest:tt)* },)+) => (
-        turing!($default ; $initial ; $accept ; $(($mem) { $($rest)* }),+)
+    ($default:expr ; $initial:expr ; [$($accept:expr),*] ; [$($reject:expr),*] ; $(($mem:expr) { $($rest:tt)* },)+) => (
+        turing!($default ; $initial ; [$($accept),*] ; [$($reject),*] ; $(($mem) { $($rest)* }),+)
     );
-    ($default:expr ; $initial:expr ; $accept:expr ; $(($mem:expr) { $($sym:expr => ($dir:expr, $sym_new:expr, $mem_new:expr))* }),+) => ({
+    ($default:expr ; $initial:expr ; [$($accept:expr),*] ; [$($reject:expr),*] ; $(($mem:expr) { $($sym:expr => ($dir:expr, $sym_new:expr, $mem_new:expr))* }),+) => ({
         let mut map = std::collections::HashMap::new();
         $($(
             map.insert(($sym, $mem), ($dir, $sym_new, $mem_new));
-        )+)+
-        $crate::Turing::new(map, $default, $initial, $accept)
+        )*)+
+        $crate::Turing::new(map, $default, $initial, vec![$($accept),*], vec![$($reject),*])
     });
-}
\ No newline at end of file
+    ($default:expr ; $initial:expr ; $accept:expr ; $(($mem:expr) { $($rest:tt)* },)+) => (
+        turing!($default ; $initial ; [$accept] ; [] ; $(($mem) { $($rest)* }),+)
+    );
+    ($default:expr ; $initial:expr ; $accept:expr ; $(($mem:expr) { $($rest:tt)* }),+) => (
+        turing!($default ; $initial ; [$accept] ; [] ; $(($mem) { $($rest)* }),+)
+    );
+}
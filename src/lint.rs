@@ -0,0 +1,122 @@
+use std::collections::{ HashSet, VecDeque };
+use std::fmt::{ self, Display, Formatter };
+use std::hash::Hash;
+
+use crate::{ Label, Space, Turing };
+
+/// A likely mistake in a transition table, as found by `Turing::validate`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub enum Lint<Sym, Mem> {
+    /// The state is entered by some transition, but has no transitions of its own and is neither
+    /// accepting nor rejecting, so the machine always gets stuck in it.
+    Dead(Mem),
+    /// The state can not be reached from the initial state.
+    Unreachable(Mem),
+    /// The transition leaves an accepting or rejecting state, so it never fires.
+    FromFinal(Mem, Sym),
+    /// The symbol is written, but no transition reads it.
+    Unread(Sym),
+}
+
+impl<Sym: Label, Mem: Label> Display for Lint<Sym, Mem> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Lint::Dead(mem) => write!(f, "state `{}` is entered but has no transitions", mem.label()),
+            Lint::Unreachable(mem) => write!(f, "state `{}` can not be reached from the initial state", mem.label()),
+            Lint::FromFinal(mem, sym) => write!(f, "transition from final state `{}` reading `{}` never fires", mem.label(), sym.label()),
+            Lint::Unread(sym) => write!(f, "symbol `{}` is written but never read", sym.label()),
+        }
+    }
+}
+
+impl<S: Space, Mem> Turing<S, Mem>
+    where S::Sym: Hash + Ord, Mem: Clone + Ord + Hash {
+    /// Checks the transition table for states and transitions that can not be used as intended.
+    /// Symbols are compared whole, so a multi-tape machine is checked on its symbol vectors.
+    /// The lints are sorted by kind, then by the states and symbols involved.
+    pub fn validate(&self) -> Vec<Lint<S::Sym, Mem>> {
+        let mut lints = Vec::new();
+        let sources: HashSet<&Mem> = self.map.keys().map(|(_, mem)| mem).collect();
+        let read: HashSet<&S::Sym> = self.map.keys().map(|(sym, _)| sym).collect();
+        let final_state = |mem: &Mem| self.accepting.contains(mem) || self.rejecting.contains(mem);
+
+        let mut dead = HashSet::new();
+        let mut unread = HashSet::new();
+        for (_, sym, mem) in self.map.values() {
+            if !sources.contains(mem) && !final_state(mem) && dead.insert(mem) {
+                lints.push(Lint::Dead(mem.clone()));
+            }
+            if !read.contains(sym) && unread.insert(sym) {
+                lints.push(Lint::Unread(sym.clone()));
+            }
+        }
+
+        let mut reached = HashSet::new();
+        let mut queue = VecDeque::new();
+        reached.insert(&self.initial);
+        queue.push_back(&self.initial);
+        while let Some(mem) = queue.pop_front() {
+            if final_state(mem) {
+                continue;
+            }
+            for ((_, from), (_, _, to)) in &self.map {
+                if from == mem && reached.insert(to) {
+                    queue.push_back(to);
+                }
+            }
+        }
+
+        let states = sources.iter().copied()
+            .chain(self.map.values().map(|(_, _, mem)| mem))
+            .chain(&self.accepting)
+            .chain(&self.rejecting);
+        let mut unreachable = HashSet::new();
+        for mem in states {
+            if !reached.contains(mem) && unreachable.insert(mem) {
+                lints.push(Lint::Unreachable(mem.clone()));
+            }
+        }
+
+        for (sym, mem) in self.map.keys() {
+            if final_state(mem) {
+                lints.push(Lint::FromFinal(mem.clone(), sym.clone()));
+            }
+        }
+
+        lints.sort();
+        lints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lint;
+    use crate::{ Tape, Turing, turing, Dir::* };
+
+    #[test]
+    fn finds_each_kind_of_mistake_in_order() {
+        let t: Turing<Tape<char>, &str> = turing!('_';"a";["h"];["r"];
+            ("a") { '_' => (Right, '1', "b") '1' => (Right, '2', "c") },
+            ("b") { '_' => (Left , 'x', "h") },
+            ("h") { '_' => (Stay , '_', "a") },
+            ("z") { '_' => (Stay , 'y', "r") },
+        );
+        assert_eq!(t.validate(), vec![
+            Lint::Dead("c"),
+            Lint::Unreachable("r"),
+            Lint::Unreachable("z"),
+            Lint::FromFinal("h", '_'),
+            Lint::Unread('2'),
+            Lint::Unread('x'),
+            Lint::Unread('y'),
+        ]);
+    }
+
+    #[test]
+    fn labels_symbols_and_states() {
+        let unread: Lint<[char; 2], String> = Lint::Unread(['1', '_']);
+        assert_eq!(unread.to_string(), "symbol `1_` is written but never read");
+        let dead: Lint<char, String> = Lint::Dead("b".to_owned());
+        assert_eq!(dead.to_string(), "state `b` is entered but has no transitions");
+    }
+}
@@ -1,40 +1,482 @@
 extern crate turing;
 
 use std::fmt::Display;
+use std::fs::File;
+use std::io::{ self, BufRead, BufWriter, Write };
 use std::thread::sleep;
 use std::time::Duration;
-use turing::{ Tape, turing, Dir::* };
+use std::hash::Hash;
+use turing::{ Breakpoint, Fuel, Halt, Label, Rewind, Space, SvgOptions, Tape, Tapes, Turing, Verdict, turing, Dir::* };
+
+const USAGE: &str = "\
+usage: turing_bin [OPTIONS] MACHINE [INPUT...]
+       turing_bin [OPTIONS] --builtin NAME [INPUT]
+
+MACHINE is a file with a machine definition, see machines/ for examples.
+Each INPUT is written to its own tape, in order. Tapes without input start blank.
+
+Options:
+    --tapes N           number of tapes MACHINE uses (default 1)
+    --input-file PATH   write each line of PATH to its own tape instead of INPUT
+    --builtin NAME      run a builtin machine: mul, sqr, copy or calc
+    --delay MS          pause between steps in milliseconds (default 200)
+    --quiet             only print the final tapes
+    --max-steps N       give up after N steps
+    --output WHAT       what to print at the end: all, tape, state or steps
+    --dot               print the state diagram in Graphviz DOT instead of running
+    --svg PATH          write a space-time diagram of the run to PATH instead of animating it
+    --svg-every K       only draw every K-th step in the diagram
+    --svg-crop          only draw the cells the head visited in the diagram
+    --trace PATH        write a trace of the run to PATH as JSON lines instead of animating it
+    --interactive       step through the run with commands read from stdin, see `help`
+    --detect-cycles     stop with exit status 4 once the machine repeats a configuration,
+                        or on a single tape repeats itself while drifting along it
+
+Exit status is 0 when the machine accepts, 1 when it rejects, 2 when it gets
+stuck, 3 when it runs out of steps, 4 when --detect-cycles finds a cycle, 64 on
+invalid usage and 74 when an output file cannot be written.";
+
+struct Options {
+    delay: Duration,
+    quiet: bool,
+    max_steps: Option<usize>,
+    output: Output,
+    dot: bool,
+    svg: Option<String>,
+    svg_options: SvgOptions,
+    trace: Option<String>,
+    interactive: bool,
+    detect_cycles: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Output {
+    All,
+    Tape,
+    State,
+    Steps,
+}
 
 fn main() {
-    let mut args = ::std::env::args().skip(1);
-    let machine = args.next().unwrap_or("copy".to_owned());
-    let input = args.next();
-    let input = input.as_ref().map(|s|s.as_ref());
-
-    match machine.as_ref() {
-        "mul" => unary_mul(input),
-        "sqr" => check_sqr(input),
-        "copy" => copy(input),
-        "calc" => calcuator(input),
-        m => println!("Unknown machine: {}", m),
+    match run(std::env::args().skip(1)) {
+        Ok(code) => std::process::exit(code),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            eprintln!();
+            eprintln!("{}", USAGE);
+            std::process::exit(64);
+        }
     }
 }
 
-fn print_state<Sym: Display, Mem: Display>(tape: &Tape<Sym>, mem: &Mem) {
-    println!("mem: {}", mem);
-    println!("{}", tape);
-    sleep(Duration::from_millis(200));
+fn run<I: Iterator<Item = String>>(mut args: I) -> Result<i32, String> {
+    let mut tapes = 1;
+    let mut input_file = None;
+    let mut builtin = None;
+    let mut positional = Vec::new();
+    let mut output = None;
+    let mut opts = Options { delay: Duration::from_millis(200), quiet: false, max_steps: None, output: Output::All, dot: false, svg: None, svg_options: SvgOptions::default(), trace: None, interactive: false, detect_cycles: false };
+
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--tapes" => tapes = number(&mut args, "--tapes")?,
+            "--input-file" => input_file = Some(value(&mut args, "--input-file")?),
+            "--builtin" => builtin = Some(value(&mut args, "--builtin")?),
+            "--delay" => opts.delay = Duration::from_millis(number(&mut args, "--delay")? as u64),
+            "--quiet" => opts.quiet = true,
+            "--dot" => opts.dot = true,
+            "--svg" => opts.svg = Some(value(&mut args, "--svg")?),
+            "--svg-every" => opts.svg_options.every = number(&mut args, "--svg-every")?,
+            "--svg-crop" => opts.svg_options.crop = true,
+            "--trace" => opts.trace = Some(value(&mut args, "--trace")?),
+            "--interactive" => opts.interactive = true,
+            "--detect-cycles" => opts.detect_cycles = true,
+            "--max-steps" => opts.max_steps = Some(number(&mut args, "--max-steps")?),
+            "--output" => output = Some(match value(&mut args, "--output")?.as_ref() {
+                "all" => Output::All,
+                "tape" => Output::Tape,
+                "state" => Output::State,
+                "steps" => Output::Steps,
+                o => return Err(format!("unknown output {}, expected all, tape, state or steps", o)),
+            }),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(0);
+            }
+            a if a.starts_with("--") => return Err(format!("unknown option {}", a)),
+            _ => positional.push(arg),
+        }
+    }
+    if [opts.svg.is_some(), opts.trace.is_some(), opts.interactive, opts.detect_cycles].iter().filter(|&&on| on).count() > 1 {
+        return Err("only one of --svg, --trace, --interactive and --detect-cycles can be given".to_owned());
+    }
+    opts.output = output.unwrap_or(if opts.quiet { Output::Tape } else { Output::All });
+
+    if let Some(name) = builtin {
+        let input = positional.first().map(|s|s.as_ref());
+        return match name.as_ref() {
+            "mul" => Ok(unary_mul(input, &opts)),
+            "sqr" => Ok(check_sqr(input, &opts)),
+            "copy" => Ok(copy(input, &opts)),
+            "calc" => Ok(calcuator(input, &opts)),
+            m => Err(format!("unknown builtin machine {}", m)),
+        };
+    }
+
+    if tapes == 0 {
+        return Err("--tapes expects a positive number".to_owned());
+    }
+
+    let mut positional = positional.into_iter();
+    let path = positional.next().ok_or("missing machine definition")?;
+    let src = std::fs::read_to_string(&path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+    let inputs = match input_file {
+        Some(file) => std::fs::read_to_string(&file)
+            .map_err(|e| format!("cannot read {}: {}", file, e))?
+            .lines()
+            .map(str::to_owned)
+            .collect(),
+        None => positional.collect::<Vec<_>>(),
+    };
+    if inputs.len() > tapes {
+        return Err(format!("{} inputs given for {} tapes", inputs.len(), tapes));
+    }
+
+    if tapes == 1 {
+        let t = turing::parse::parse(&src).map_err(|e| format!("{}:{}", path, e))?;
+        for lint in t.validate() {
+            eprintln!("warning: {}: {}", path, lint);
+        }
+        let space = Tape::new(*t.blank(), inputs.first().into_iter().flat_map(|input| input.chars()));
+        return Ok(simulate(&t, space, &opts));
+    }
+
+    let t = turing::parse::parse_vec(&src, tapes).map_err(|e| format!("{}:{}", path, e))?;
+    for lint in t.validate() {
+        eprintln!("warning: {}: {}", path, lint);
+    }
+    let space = t.blank().iter().enumerate()
+        .map(|(i, blank)| Tape::new(*blank, inputs.get(i).into_iter().flat_map(|input| input.chars())))
+        .collect::<Vec<_>>();
+
+    Ok(simulate(&t, space, &opts))
+}
+
+fn value<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("{} expects a value", flag))
 }
 
-fn print_slices<Sym: Display, Mem: Display, A: AsRef<[Tape<Sym>]>>(tape: &A, mem: &Mem) {
+fn number<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<usize, String> {
+    value(args, flag)?.parse().map_err(|_| format!("{} expects a number", flag))
+}
+
+/// How `--detect-cycles` looks for a run that never halts.
+trait Detect<Mem>: Space + Sized {
+    fn detect(t: &Turing<Self, Mem>, space: Self, fuel: Fuel) -> Verdict<Self, Mem>;
+}
+
+/// A single tape can also drift while repeating itself, which `detect_cycle` never notices, and
+/// a pure cycle never gets past `detect_translated_cycle`. So they take turns, with the step
+/// budget doubling each round.
+impl<Mem: Clone + Eq + Hash> Detect<Mem> for Tape<char> {
+    fn detect(t: &Turing<Self, Mem>, space: Self, fuel: Fuel) -> Verdict<Self, Mem> {
+        let mut budget = 1 << 10;
+        loop {
+            let capped = fuel.steps.is_none_or(|steps| budget < steps);
+            let limited = Fuel { steps: Some(fuel.steps.map_or(budget, |steps| steps.min(budget))), ..fuel };
+            let verdict = t.detect_cycle(space.clone(), limited);
+            if !matches!(verdict, Verdict::Halted(Halt::OutOfFuel { .. })) {
+                return verdict;
+            }
+            match t.detect_translated_cycle(space.clone(), limited) {
+                Verdict::Halted(halt @ Halt::OutOfFuel { .. }) if capped && halt.stats().steps >= budget => budget *= 2,
+                verdict => return verdict,
+            }
+        }
+    }
+}
+
+impl<Mem: Clone + Eq + Hash, const N: usize> Detect<Mem> for [Tape<char>; N]
+    where [Tape<char>; N]: Space + Clone + PartialEq, <[Tape<char>; N] as Space>::Sym: Hash {
+    fn detect(t: &Turing<Self, Mem>, space: Self, fuel: Fuel) -> Verdict<Self, Mem> {
+        t.detect_cycle(space, fuel)
+    }
+}
+
+impl<Mem: Clone + Eq + Hash> Detect<Mem> for Vec<Tape<char>> {
+    fn detect(t: &Turing<Self, Mem>, space: Self, fuel: Fuel) -> Verdict<Self, Mem> {
+        t.detect_cycle(space, fuel)
+    }
+}
+
+fn simulate<S, Mem>(t: &Turing<S, Mem>, space: S, opts: &Options) -> i32
+    where S: Tapes<Cell = char> + Detect<Mem> + Clone + Eq, S::Sym: Hash + Label, S::Dir: Label, Mem: Display + Label + Clone + Eq + Hash {
+    if opts.dot {
+        print!("{}", t.to_dot());
+        return 0;
+    }
+
+    let fuel = opts.max_steps.map_or(Fuel::unlimited(), Fuel::steps);
+    let halt = if let Some(path) = &opts.svg {
+        let (halt, diagram) = t.space_time(space, fuel, opts.svg_options);
+        if let Err(e) = std::fs::write(path, diagram.to_svg()) {
+            eprintln!("error: cannot write {}: {}", path, e);
+            return 74;
+        }
+        halt
+    } else if let Some(path) = &opts.trace {
+        match File::create(path).and_then(|file| t.trace(space, fuel, BufWriter::new(file))) {
+            Ok(halt) => halt,
+            Err(e) => {
+                eprintln!("error: cannot write {}: {}", path, e);
+                return 74;
+            }
+        }
+    } else if opts.interactive {
+        interactive(t, space, fuel)
+    } else if opts.detect_cycles {
+        match S::detect(t, space, fuel) {
+            Verdict::Halted(halt) => halt,
+            Verdict::Cycle { start, period, space, mem } => {
+                println!("Loops in {} from step {} every {} steps", mem, start, period);
+                space.tapes().iter().for_each(|tape| println!("{}", tape));
+                return 4;
+            }
+            Verdict::Translated { start, period, shift, space, mem } => {
+                println!("Loops in {} from step {} every {} steps, moving {} cells", mem, start, period, shift);
+                space.tapes().iter().for_each(|tape| println!("{}", tape));
+                return 4;
+            }
+        }
+    } else {
+        t.start(space).debug(fuel, |space, mem| {
+            if !opts.quiet {
+                print_frame(space, mem);
+                sleep(opts.delay);
+            }
+        })
+    };
+
+    let (code, outcome) = match &halt {
+        Halt::Accepted { .. } => (0, "Accepted"),
+        Halt::Rejected { .. } => (1, "Rejected"),
+        Halt::Stuck { .. } => (2, "Failed"),
+        Halt::OutOfFuel { .. } => (3, "Gave up"),
+    };
+    match opts.output {
+        Output::All => {
+            println!("{} in {} after {} steps", outcome, halt.mem(), halt.stats().steps);
+            halt.space().tapes().iter().for_each(|tape| println!("{}", tape));
+        }
+        Output::Tape => halt.space().tapes().iter().for_each(|tape| println!("{}", tape)),
+        Output::State => println!("{}", halt.mem()),
+        Output::Steps => println!("{}", halt.stats().steps),
+    }
+    code
+}
+
+fn print_frame<S: Tapes<Cell = char>, Mem: Display>(space: &S, mem: &Mem) {
     println!("mem: {}", mem);
-    for t in tape.as_ref() {
+    for t in space.tapes() {
         println!("{}", t);
     }
-    sleep(Duration::from_millis(200));
 }
 
-fn unary_mul(input: Option<&str>) {
+const COMMANDS: &str = "\
+commands:
+    s, step [N]                 take N steps (default 1)
+    b, back [N]                 undo N steps (default 1)
+    c, continue                 run to the next breakpoint
+    r, run STATE                run until STATE is entered
+    g, goto N                   go forward or back to step N
+    break                       list breakpoints
+    break state STATE           pause when STATE is entered
+    break transition STATE SYM  pause when STATE reads SYM
+    break cell [TAPE] CELL      pause when CELL changes
+    break head [TAPE] CELL      pause when the head moves onto CELL
+    break step N                pause after N steps
+    delete N                    remove breakpoint N
+    t, transitions              show the transitions of the current state
+    p, print [RADIUS]           show the cells around the heads (default 10)
+    q, quit                     stop and report the current configuration";
+
+fn interactive<S, Mem>(t: &Turing<S, Mem>, space: S, fuel: Fuel) -> Halt<S, Mem>
+    where S: Tapes<Cell = char> + Clone, S::Sym: Hash + Label, S::Dir: Label, Mem: Display + Label + Clone + Eq + Hash {
+    let mut rewind = Rewind::new(t.start(space));
+    let mut breakpoints = Vec::new();
+    let mut radius = 10;
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    print_window(&rewind, radius);
+    loop {
+        print!("> ");
+        io::stdout().flush().ok();
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => {
+                println!();
+                break;
+            }
+        };
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => continue,
+            ["q" | "quit"] => break,
+            words => {
+                let steps = rewind.stats().steps;
+                match command(t, &mut rewind, &mut breakpoints, &mut radius, words, fuel) {
+                    Ok(()) if rewind.stats().steps != steps => print_window(&rewind, radius),
+                    Ok(()) => (),
+                    Err(e) => println!("{}", e),
+                }
+            }
+        }
+    }
+    rewind.into_execution().into_halt()
+}
+
+fn command<S, Mem>(t: &Turing<S, Mem>, rewind: &mut Rewind<S, Mem>, breakpoints: &mut Vec<Breakpoint<S::Sym, Mem>>, radius: &mut usize, words: &[&str], fuel: Fuel) -> Result<(), String>
+    where S: Tapes<Cell = char> + Clone, S::Sym: Hash + Label, S::Dir: Label, Mem: Display + Label + Clone + Eq + Hash {
+    match words {
+        ["h" | "help"] => println!("{}", COMMANDS),
+        ["s" | "step"] => step(rewind, 1, fuel),
+        ["s" | "step", n] => step(rewind, count(n)?, fuel),
+        ["b" | "back"] => back(rewind, 1),
+        ["b" | "back", n] => back(rewind, count(n)?),
+        ["c" | "continue"] => run_to(rewind, breakpoints, fuel),
+        ["r" | "run", name] => run_to(rewind, &[Breakpoint::State(find_state(t, name)?)], fuel),
+        ["g" | "goto", n] => if !rewind.seek(count(n)?, fuel) {
+            if fuel.exhausted(&rewind.stats()) {
+                println!("out of fuel at step {}", rewind.stats().steps);
+            } else {
+                println!("stopped at step {}", rewind.stats().steps);
+            }
+        },
+        ["break"] => breakpoints.iter().enumerate().for_each(|(i, b)| println!("{}: {}", i, describe(b))),
+        ["break", rest @ ..] => {
+            let b = breakpoint(t, rest)?;
+            println!("{}: {}", breakpoints.len(), describe(&b));
+            breakpoints.push(b);
+        }
+        ["delete", n] => {
+            let n = count(n)?;
+            if n >= breakpoints.len() {
+                return Err(format!("no breakpoint {}", n));
+            }
+            breakpoints.remove(n);
+        }
+        ["t" | "transitions"] => print_transitions(t, rewind),
+        ["p" | "print"] => print_window(rewind, *radius),
+        ["p" | "print", r] => {
+            *radius = count(r)?;
+            print_window(rewind, *radius);
+        }
+        _ => return Err(format!("unknown command `{}`, try `help`", words.join(" "))),
+    }
+    Ok(())
+}
+
+fn count(n: &str) -> Result<usize, String> {
+    n.parse().map_err(|_| format!("expected a number, found `{}`", n))
+}
+
+fn step<S, Mem>(rewind: &mut Rewind<S, Mem>, n: usize, fuel: Fuel)
+    where S: Tapes + Clone, S::Sym: Hash, S::Cell: Clone, Mem: Clone + Eq + Hash {
+    for _ in 0..n {
+        if fuel.exhausted(&rewind.stats()) || rewind.step().is_none() {
+            println!("halted");
+            break;
+        }
+    }
+}
+
+fn back<S, Mem>(rewind: &mut Rewind<S, Mem>, n: usize)
+    where S: Tapes + Clone, S::Sym: Hash, S::Cell: Clone, Mem: Clone + Eq + Hash {
+    for _ in 0..n {
+        if !rewind.step_back() {
+            println!("at the start");
+            break;
+        }
+    }
+}
+
+fn run_to<S, Mem>(rewind: &mut Rewind<S, Mem>, breakpoints: &[Breakpoint<S::Sym, Mem>], fuel: Fuel)
+    where S: Tapes + Clone, S::Sym: Hash + Label, S::Cell: Clone + PartialEq, Mem: Clone + Eq + Hash + Label {
+    match rewind.run_to(breakpoints, fuel) {
+        Some(i) => println!("hit {}", describe(&breakpoints[i])),
+        None => println!("halted"),
+    }
+}
+
+fn find_state<S: Tapes, Mem: Label + Clone>(t: &Turing<S, Mem>, name: &str) -> Result<Mem, String> {
+    std::iter::once(t.initial())
+        .chain(t.transitions().iter().flat_map(|((_, mem), (_, _, mem_new))| vec![mem, mem_new]))
+        .find(|mem| mem.label() == name)
+        .cloned()
+        .ok_or_else(|| format!("no state `{}`", name))
+}
+
+fn breakpoint<S, Mem>(t: &Turing<S, Mem>, words: &[&str]) -> Result<Breakpoint<S::Sym, Mem>, String>
+    where S: Tapes, S::Sym: Label, Mem: Label + Clone {
+    let cell = |tape: &str, cell: &str| -> Result<(usize, isize), String> {
+        let tape = count(tape)?;
+        let cell = cell.parse().map_err(|_| format!("expected a cell, found `{}`", cell))?;
+        Ok((tape, cell))
+    };
+    match words {
+        ["state", name] => find_state(t, name).map(Breakpoint::State),
+        ["transition", name, sym] => t.transitions().keys()
+            .find(|(s, mem)| mem.label() == *name && s.label() == *sym)
+            .map(|(s, mem)| Breakpoint::Transition(mem.clone(), s.clone()))
+            .ok_or_else(|| format!("no transition from `{}` reading `{}`", name, sym)),
+        ["cell", c] => cell("0", c).map(|(tape, cell)| Breakpoint::Cell { tape, cell }),
+        ["cell", tape, c] => cell(tape, c).map(|(tape, cell)| Breakpoint::Cell { tape, cell }),
+        ["head", c] => cell("0", c).map(|(tape, cell)| Breakpoint::Head { tape, cell }),
+        ["head", tape, c] => cell(tape, c).map(|(tape, cell)| Breakpoint::Head { tape, cell }),
+        ["step", n] => count(n).map(Breakpoint::Steps),
+        _ => Err("expected state, transition, cell, head or step, try `help`".to_owned()),
+    }
+}
+
+fn describe<Sym: Label, Mem: Label>(b: &Breakpoint<Sym, Mem>) -> String {
+    match b {
+        Breakpoint::State(mem) => format!("entering {}", mem.label()),
+        Breakpoint::Transition(mem, sym) => format!("{} reading {}", mem.label(), sym.label()),
+        Breakpoint::Cell { tape, cell } => format!("cell {} of tape {} changing", cell, tape),
+        Breakpoint::Head { tape, cell } => format!("head of tape {} reaching cell {}", tape, cell),
+        Breakpoint::Steps(n) => format!("step {}", n),
+    }
+}
+
+fn print_transitions<S, Mem>(t: &Turing<S, Mem>, rewind: &Rewind<S, Mem>)
+    where S: Tapes + Clone, S::Sym: Hash + Label, S::Dir: Label, S::Cell: Clone, Mem: Label + Clone + Eq + Hash {
+    let read = rewind.space().read();
+    let mut lines: Vec<String> = t.transitions().iter()
+        .filter(|((_, mem), _)| mem == rewind.state())
+        .map(|((sym, _), (dir, sym_new, mem_new))| {
+            let mark = if *sym == read { "*" } else { " " };
+            format!("{} {} -> {} {} {}", mark, sym.label(), mem_new.label(), sym_new.label(), dir.label())
+        })
+        .collect();
+    lines.sort_by(|a, b| a[2..].cmp(&b[2..]));
+    if lines.is_empty() {
+        println!("no transitions from {}", rewind.state().label());
+    }
+    lines.iter().for_each(|line| println!("{}", line));
+}
+
+fn print_window<S, Mem>(rewind: &Rewind<S, Mem>, radius: usize)
+    where S: Tapes<Cell = char> + Clone, S::Sym: Hash, Mem: Display + Clone + Eq + Hash {
+    println!("step {}, state {}", rewind.stats().steps, rewind.state());
+    for tape in rewind.space().tapes() {
+        let offset = (tape.head() - tape.bounds().start).min(radius as isize) as usize;
+        println!("{}", tape.window(radius).collect::<String>());
+        println!("{:>1$}", "^", offset + 1);
+    }
+}
+
+fn unary_mul(input: Option<&str>, opts: &Options) -> i32 {
     let input = input.unwrap_or("#111#1111#");
     let t = turing!(['_','_','_'];"Start";"Done";
         ("Start") {['#','_','_'] => ([Right, Right, Stay], ['#', '#', '_'], "Par1")},
@@ -85,45 +527,37 @@ fn unary_mul(input: Option<&str>) {
     let tape = input.chars().collect();
     let tape2 = "_".chars().collect();
     let tape3 = "_".chars().collect();
-    match t.debug([tape, tape2, tape3], print_slices) {
-        None => println!("Failed"),
-        Some([tape, tape2, tape3]) => {
-            println!("Finished as");
-            println!("{}", tape);
-            println!("{}", tape2);
-            println!("{}", tape3);
-        }
-    }
+    simulate(&t, [tape, tape2, tape3], opts)
 }
 
-fn check_sqr(input: Option<&str>) {
+fn check_sqr(input: Option<&str>, opts: &Options) -> i32 {
     let input = input.unwrap_or("1111111111111111");
-    let t = turing!(['_','_'];"Start";"Done";
-        ("Start") { ['1','_'] => ([Right, Stay], ['1', '_'], "Reset") },
+    simulate(&square(), [Tape::new('_', input.chars()), Tape::new('_', None)], opts)
+}
+
+/// Decides whether a unary number is a perfect square.
+fn square() -> Turing<[Tape<char>; 2], &'static str> {
+    turing!(['_','_'];"Start";["Done"];["Reject"];
+        ("Start") {
+            ['1','_'] => ([Right, Stay], ['1', '_'], "Reset")
+            ['_','_'] => ([Stay, Stay], ['_', '_'], "Done")
+        },
         ("Bump") {
             ['1','1'] => ([Right, Right], ['1', '1'], "Bump")
             ['1','_'] => ([Right, Stay], ['1', '1'], "Reset")
+            ['_','1'] => ([Stay, Stay], ['_', '1'], "Reject")
+            ['_','_'] => ([Stay, Stay], ['_', '_'], "Reject")
         },
         ("Reset") {
             ['1','1'] => ([Right, Left], ['1', '1'], "Reset")
             ['1','_'] => ([Right, Right], ['1', '_'], "Bump")
             ['_','_'] => ([Stay, Right], ['_', '_'], "Done")
+            ['_','1'] => ([Stay, Stay], ['_', '1'], "Reject")
         },
-    );
-
-    let tape = input.chars().collect();
-    let tape2 = "_".chars().collect();
-    match t.debug([tape, tape2], print_slices) {
-        None => println!("Failed"),
-        Some([tape, tape2]) => {
-            println!("Finished as");
-            println!("{}", tape);
-            println!("{}", tape2);
-        }
-    }
+    )
 }
 
-fn copy(input: Option<&str>) {
+fn copy(input: Option<&str>, opts: &Options) -> i32 {
     let input = input.unwrap_or("_abbaab");
     let t = turing! ('_';0;9;
         (0) { '_' => (Right, '_', 1) },
@@ -170,19 +604,20 @@ fn copy(input: Option<&str>) {
         },
     );
 
-    let tape = input.chars().collect();
-    match t.debug(tape, print_state) {
-        None => println!("Failed"),
-        Some(state) => {
-            println!("Finished as");
-            println!("{}", state)
-        }
-    }
+    let tape: Tape<char> = input.chars().collect();
+    simulate(&t, tape, opts)
 }
 
-fn calcuator(input: Option<&str>) {
+fn calcuator(input: Option<&str>, opts: &Options) -> i32 {
     let input = input.unwrap_or("_uucz1100,0101");
-    let t = turing! ('_';"Start";"Done";
+    let tape: Tape<char> = input.chars().collect();
+    simulate(&calculator(), tape, opts)
+}
+
+/// A calculator for binary numbers, which applies the operators before the operands from right
+/// to left. `-` rejects when the second operand is larger than the first, instead of wrapping.
+fn calculator() -> Turing<Tape<char>, &'static str> {
+    turing! ('_';"Start";["Done"];["Reject"];
         ("Start") { '_' => (Right, '_', "Start2") },
         ("Start2") {
             '0' => (Right, '0', "Start2")
@@ -214,6 +649,7 @@ fn calcuator(input: Option<&str>) {
             '|' => (Right, ' ', "Or")
             '&' => (Right, ' ', "And")
             '+' => (Right, ' ', "Add")
+            '-' => (Right, ' ', "Sub")
         },
         ("Space") {
             '0' => (Right, '0', "Space")
@@ -416,14 +852,138 @@ fn calcuator(input: Option<&str>) {
             ',' => (Left , ',', "Add2")
             '_' => (Left , '_', "Add2")
         },
-    );
+        ("Add2") {
+            '0' => (Left , ' ', "Add:0")
+            '1' => (Left , ' ', "Add:1")
+            ' ' => (Right, ' ', "Start2")
+        },
+        ("Add2:1") {
+            '0' => (Left , ' ', "Add:1")
+            '1' => (Left , ' ', "Add:2")
+            ' ' => (Right, '1', "Start2")
+        },
+        ("Add:0") {
+            '0' => (Left , '0', "Add2")
+            '1' => (Left , '1', "Add2")
+        },
+        ("Add:1") {
+            '0' => (Left , '1', "Add2")
+            '1' => (Left , '0', "Add2:1")
+        },
+        ("Add:2") {
+            '0' => (Left , '0', "Add2:1")
+            '1' => (Left , '1', "Add2:1")
+        },
+        ("Sub") {
+            '0' => (Right, '0', "Sub")
+            '1' => (Right, '1', "Sub")
+            ',' => (Left , ',', "Sub2")
+            '_' => (Left , '_', "Sub2")
+        },
+        ("Sub2") {
+            '0' => (Left , ' ', "Sub:0")
+            '1' => (Left , ' ', "Sub:1")
+            ' ' => (Right, ' ', "Start2")
+        },
+        ("Sub2:1") {
+            '0' => (Left , ' ', "Sub:1")
+            '1' => (Left , ' ', "Sub:2")
+            ' ' => (Stay , ' ', "Reject")
+        },
+        ("Sub:0") {
+            '0' => (Left , '0', "Sub2")
+            '1' => (Left , '1', "Sub2")
+        },
+        ("Sub:1") {
+            '0' => (Left , '1', "Sub2:1")
+            '1' => (Left , '0', "Sub2")
+        },
+        ("Sub:2") {
+            '0' => (Left , '0', "Sub2:1")
+            '1' => (Left , '1', "Sub2:1")
+        },
+    )
+}
 
-    let tape = input.chars().collect();
-    match t.debug(tape, print_state) {
-        None => println!("Failed"),
-        Some(state) => {
-            println!("Finished as");
-            println!("{}", state)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calc(input: &str) -> Halt<Tape<char>, &'static str> {
+        calculator().run_with_limit(input.chars().collect(), 10_000)
+    }
+
+    fn result(halt: &Halt<Tape<char>, &'static str>) -> String {
+        halt.space().trimmed(&'_').collect()
+    }
+
+    #[test]
+    fn adds() {
+        for (input, sum) in [("_+z11,01", "100"), ("_+z1100,0101", "10001"), ("_+z0,0", "0"), ("_+z0110,0011", "1001")] {
+            let halt = calc(input);
+            assert!(halt.is_accepted(), "{} was not accepted", input);
+            assert_eq!(result(&halt), sum, "{}", input);
+        }
+    }
+
+    #[test]
+    fn subtracts() {
+        for (input, difference) in [("_-z0110,0011", "0011"), ("_-z11,01", "10"), ("_-z0011,0011", "0000"), ("_-z1000,0001", "0111")] {
+            let halt = calc(input);
+            assert!(halt.is_accepted(), "{} was not accepted", input);
+            assert_eq!(result(&halt), difference, "{}", input);
+        }
+    }
+
+    fn assert_decides_squares<Mem: Clone + Eq + Hash>(t: &Turing<[Tape<char>; 2], Mem>) {
+        for n in 0..40 {
+            let input = [Tape::new('_', "1".repeat(n).chars()), Tape::new('_', None)];
+            let halt = t.run_with_limit(input, 10_000);
+            let root = (n as f64).sqrt() as usize;
+            if root * root == n {
+                assert!(halt.is_accepted(), "{} was not accepted", n);
+            } else {
+                assert!(halt.is_rejected(), "{} was not rejected", n);
+            }
+        }
+    }
+
+    #[test]
+    fn decides_squares() {
+        assert_decides_squares(&square());
+    }
+
+    #[test]
+    fn machine_file_decides_squares() {
+        assert_decides_squares(&turing::parse::parse_array::<2>(include_str!("../machines/sqr.tm")).unwrap());
+    }
+
+    #[test]
+    fn detects_drifting_on_a_single_tape() {
+        let t = turing!('_';'a';'h';
+            ('a') { '_' => (Right, '1', 'a') },
+        );
+        match Tape::detect(&t, Tape::new('_', None), Fuel::unlimited()) {
+            Verdict::Translated { start, period, shift, .. } => assert_eq!((start, period, shift), (0, 1, 1)),
+            _ => panic!("expected a translated cycle"),
+        }
+        assert!(matches!(Tape::detect(&t, Tape::new('_', None), Fuel::steps(5000)), Verdict::Translated { .. }));
+        assert!(matches!(Tape::detect(&t, Tape::new('_', None), Fuel::steps(0)), Verdict::Halted(Halt::OutOfFuel { .. })));
+    }
+
+    #[test]
+    fn detects_cycles_on_a_single_tape() {
+        let t = turing!('_';'a';'h';
+            ('a') { '_' => (Right, '_', 'c') },
+            ('c') { '_' => (Left , '_', 'a') },
+        );
+        assert!(matches!(Tape::detect(&t, Tape::new('_', None), Fuel::unlimited()), Verdict::Cycle { start: 0, period: 2, .. }));
+    }
+
+    #[test]
+    fn rejects_negative_differences() {
+        for input in ["_-z0011,0110", "_-z0,1", "_-z01,10"] {
+            assert!(calc(input).is_rejected(), "{} was not rejected", input);
         }
     }
 }
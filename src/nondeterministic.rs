@@ -0,0 +1,206 @@
+use std::collections::{ HashMap, HashSet, VecDeque };
+use std::hash::Hash;
+
+use crate::{ Fuel, Space, Stats, Step };
+
+pub type Relation<S, Mem> = HashMap<(<S as Space>::Sym, Mem), Vec<(<S as Space>::Dir, <S as Space>::Sym, Mem)>>;
+
+pub struct NondeterministicTuring<S: Space, Mem> {
+    map: Relation<S, Mem>,
+    default: S::Sym,
+    initial: Mem,
+    accepting: HashSet<Mem>,
+    rejecting: HashSet<Mem>,
+}
+
+/// The outcome of exploring every branch of a `NondeterministicTuring`.
+pub enum Search<S: Space, Mem> {
+    /// Some branch accepted. `path` holds the steps leading from the initial configuration to it.
+    Accepted { space: S, mem: Mem, path: Vec<Step<S, Mem>>, explored: usize },
+    /// Every branch halted without accepting or revisited an earlier configuration.
+    Rejected { explored: usize },
+    /// Some branch was cut off by the fuel before it halted.
+    OutOfFuel { explored: usize },
+}
+
+impl<S: Space, Mem> Search<S, Mem> {
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, Search::Accepted { .. })
+    }
+
+    pub fn explored(&self) -> usize {
+        match self {
+            Search::Accepted { explored, .. } | Search::Rejected { explored } | Search::OutOfFuel { explored } => *explored,
+        }
+    }
+}
+
+struct Node<S: Space, Mem> {
+    space: S,
+    mem: Mem,
+    parent: Option<(usize, Step<S, Mem>)>,
+    depth: usize,
+}
+
+impl<S: Space, Mem> NondeterministicTuring<S, Mem> {
+    pub fn new<A, R>(map: Relation<S, Mem>, default: S::Sym, initial: Mem, accepting: A, rejecting: R) -> Self
+        where Mem: Eq + Hash, A: IntoIterator<Item = Mem>, R: IntoIterator<Item = Mem> {
+        NondeterministicTuring {
+            map,
+            default,
+            initial,
+            accepting: accepting.into_iter().collect(),
+            rejecting: rejecting.into_iter().collect(),
+        }
+    }
+
+    /// Explores the branches breadth first, so an accepting path is as short as possible.
+    /// `fuel.steps` bounds the depth of the search and `fuel.cells` the size of each branch's space.
+    /// Spaces are normalized to tell whether a branch revisits a configuration, so the accepted
+    /// space has no blanks around its content.
+    pub fn run(&self, mut space: S, fuel: Fuel) -> Search<S, Mem>
+        where S: Clone + Eq + Hash, S::Sym: Hash, Mem: Clone + Eq + Hash {
+        space.normalize(&self.default);
+        let mut nodes = vec![Node { space: space.clone(), mem: self.initial.clone(), parent: None, depth: 0 }];
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        let mut cut = false;
+        seen.insert((space, self.initial.clone()));
+        queue.push_back(0);
+
+        while let Some(i) = queue.pop_front() {
+            let node = &nodes[i];
+            if self.accepting.contains(&node.mem) {
+                return Self::accept(nodes, i);
+            }
+            if self.rejecting.contains(&node.mem) {
+                continue;
+            }
+            if fuel.exhausted(&Stats { steps: node.depth, cells: node.space.size() }) {
+                cut = true;
+                continue;
+            }
+
+            let read = node.space.read();
+            let actions = match self.map.get(&(read.clone(), node.mem.clone())) {
+                Some(actions) => actions,
+                None => continue,
+            };
+
+            let depth = node.depth + 1;
+            for (dir, write, to) in actions {
+                let node = &nodes[i];
+                let mut space = node.space.clone();
+                space.write(write.clone());
+                space.mov(dir, &self.default);
+                space.normalize(&self.default);
+                if !seen.insert((space.clone(), to.clone())) {
+                    continue;
+                }
+
+                let step = Step { from: node.mem.clone(), read: read.clone(), dir: dir.clone(), write: write.clone(), to: to.clone() };
+                nodes.push(Node { space, mem: to.clone(), parent: Some((i, step)), depth });
+                queue.push_back(nodes.len() - 1);
+            }
+        }
+
+        let explored = nodes.len();
+        if cut {
+            Search::OutOfFuel { explored }
+        } else {
+            Search::Rejected { explored }
+        }
+    }
+
+    fn accept(mut nodes: Vec<Node<S, Mem>>, i: usize) -> Search<S, Mem> {
+        let explored = nodes.len();
+        let mut path = Vec::new();
+        let mut parent = nodes[i].parent.take();
+        while let Some((j, step)) = parent {
+            path.push(step);
+            parent = nodes[j].parent.take();
+        }
+        path.reverse();
+
+        let Node { space, mem, .. } = nodes.swap_remove(i);
+        Search::Accepted { space, mem, path, explored }
+    }
+}
+
+#[macro_export]
+macro_rules! nondeterministic {
+    ($default:expr ; $initial:expr ; [$($accept:expr),*] ; [$($reject:expr),*] ; $(($mem:expr) { $($rest:tt)* },)+) => (
+        nondeterministic!($default ; $initial ; [$($accept),*] ; [$($reject),*] ; $(($mem) { $($rest)* }),+)
+    );
+    ($default:expr ; $initial:expr ; [$($accept:expr),*] ; [$($reject:expr),*] ; $(($mem:expr) { $($sym:expr => ($dir:expr, $sym_new:expr, $mem_new:expr))* }),+) => ({
+        let mut map = std::collections::HashMap::new();
+        $($(
+            map.entry(($sym, $mem)).or_insert_with(Vec::new).push(($dir, $sym_new, $mem_new));
+        )*)+
+        $crate::NondeterministicTuring::new(map, $default, $initial, vec![$($accept),*], vec![$($reject),*])
+    });
+    ($default:expr ; $initial:expr ; $accept:expr ; $(($mem:expr) { $($rest:tt)* },)+) => (
+        nondeterministic!($default ; $initial ; [$accept] ; [] ; $(($mem) { $($rest)* }),+)
+    );
+    ($default:expr ; $initial:expr ; $accept:expr ; $(($mem:expr) { $($rest:tt)* }),+) => (
+        nondeterministic!($default ; $initial ; [$accept] ; [] ; $(($mem) { $($rest)* }),+)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ Fuel, Search, Step, Tape, Dir::* };
+
+    #[test]
+    fn finds_the_shortest_accepting_path() {
+        // Guesses which cell of the input holds a `b`, the long way first.
+        let t = nondeterministic!('_';'a';'h';
+            ('a') { 'x' => (Right, 'x', 'a') 'b' => (Right, 'b', 'a') 'b' => (Stay, 'b', 'h') },
+        );
+        match t.run(Tape::new('_', "xbxb".chars()), Fuel::unlimited()) {
+            Search::Accepted { space, mem, path, .. } => {
+                assert_eq!(mem, 'h');
+                assert_eq!(space.head(), 1);
+                assert_eq!(path, vec![
+                    Step { from: 'a', read: 'x', dir: Right, write: 'x', to: 'a' },
+                    Step { from: 'a', read: 'b', dir: Stay, write: 'b', to: 'h' },
+                ]);
+            }
+            _ => panic!("expected an accepting branch"),
+        }
+        assert!(!t.run(Tape::new('_', "xxx".chars()), Fuel::unlimited()).is_accepted());
+    }
+
+    #[test]
+    fn rejects_branches_that_only_loop() {
+        let t = nondeterministic!('_';'a';'h';
+            ('a') { '_' => (Right, '_', 'b') '_' => (Left, '_', 'b') },
+            ('b') { '_' => (Left , '_', 'a') '_' => (Right, '_', 'a') }
+        );
+        match t.run(Tape::new('_', None), Fuel::unlimited()) {
+            Search::Rejected { explored } => assert_eq!(explored, 2),
+            _ => panic!("expected every branch to be rejected"),
+        }
+    }
+
+    #[test]
+    fn runs_out_of_fuel() {
+        let t = nondeterministic!('_';'a';['h'];[];
+            ('a') { '_' => (Right, '0', 'a') '_' => (Right, '1', 'a') }
+        );
+        match t.run(Tape::new('_', None), Fuel::steps(3)) {
+            Search::OutOfFuel { explored } => assert_eq!(explored, 1 + 2 + 4 + 8),
+            _ => panic!("expected the search to run out of fuel"),
+        }
+    }
+
+    #[test]
+    fn stops_branches_in_rejecting_states() {
+        let t = nondeterministic!('_';'a';['h'];['r'];
+            ('a') { '_' => (Stay, '1', 'r') '_' => (Right, '_', 'b') },
+            ('b') { '_' => (Left, '_', 'b') },
+            ('r') { '1' => (Stay, '1', 'h') },
+        );
+        assert!(matches!(t.run(Tape::new('_', None), Fuel::unlimited()), Search::Rejected { .. }));
+    }
+}
@@ -0,0 +1,378 @@
+//! A plain-text format for machine definitions.
+//!
+//! ```text
+//! // Accepts the words made of a's only.
+//! blank: _
+//! initial: start
+//! accept: done
+//! reject: fail
+//!
+//! start a -> start a R
+//! start b -> fail b S
+//! start _ -> done _ S
+//! ```
+//!
+//! A definition is a sequence of lines. Blank lines and lines starting with `//` are ignored.
+//!
+//! A header line is a key directly followed by a colon, then its value. `blank` gives the blank
+//! symbol and `initial` the initial state, and both are required. `accept` and `reject` list
+//! the accepting and rejecting states separated by whitespace. `accept` is required and
+//! `reject` is optional.
+//!
+//! Every other line is a transition: the current state, the symbols read, `->`, the next state,
+//! the symbols written and the directions to move in. A machine with `n` tapes reads and writes
+//! `n` symbols at a time, written next to each other, so `#_1` reads `#` on the first tape, `_`
+//! on the second and `1` on the third. Directions are written the same way using `L`, `R` and
+//! `S` for left, right and stay. A run of symbols can be wrapped in single quotes to include
+//! whitespace or a leading `'`, and `\` escapes the next character inside quotes. The blank is
+//! given in the same form as the symbols, either once per tape or as a single symbol shared by
+//! every tape.
+
+use std::collections::HashSet;
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt::{ self, Display, Formatter };
+
+use crate::{ Dir, Space, Tape, Turing };
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseErrorKind {
+    UnknownKey(String),
+    DuplicateKey(String),
+    MissingKey(&'static str),
+    Expected(&'static str),
+    Unexpected(String),
+    UnterminatedQuote,
+    Arity { expected: usize, found: usize },
+    InvalidDir(char),
+    DuplicateTransition,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ParseErrorKind::UnknownKey(key) => write!(f, "unknown key `{}`", key),
+            ParseErrorKind::DuplicateKey(key) => write!(f, "`{}` is given more than once", key),
+            ParseErrorKind::MissingKey(key) => write!(f, "missing `{}`", key),
+            ParseErrorKind::Expected(what) => write!(f, "expected {}", what),
+            ParseErrorKind::Unexpected(token) => write!(f, "unexpected `{}`", token),
+            ParseErrorKind::UnterminatedQuote => write!(f, "unterminated quote"),
+            ParseErrorKind::Arity { expected, found } => write!(f, "expected {} symbols, found {}", expected, found),
+            ParseErrorKind::InvalidDir(c) => write!(f, "invalid direction `{}`, expected `L`, `R` or `S`", c),
+            ParseErrorKind::DuplicateTransition => write!(f, "a transition for this state and symbol is already defined"),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl Error for ParseError {}
+
+struct Token<'a> {
+    text: &'a str,
+    column: usize,
+    quoted: bool,
+}
+
+struct Definition {
+    blank: Vec<char>,
+    initial: String,
+    accepting: Vec<String>,
+    rejecting: Vec<String>,
+    transitions: Vec<Rule>,
+}
+
+type Rule = (String, Vec<char>, String, Vec<char>, Vec<Dir>);
+
+pub fn parse(src: &str) -> Result<Turing<Tape<char>, String>, ParseError> {
+    let def = definition(src, 1)?;
+    let map = def.transitions.into_iter()
+        .map(|(mem, sym, mem_new, sym_new, dir)| ((sym[0], mem), (dir[0], sym_new[0], mem_new)))
+        .collect();
+    Ok(Turing::new(map, def.blank[0], def.initial, def.accepting, def.rejecting))
+}
+
+pub fn parse_array<const N: usize>(src: &str) -> Result<Turing<[Tape<char>; N], String>, ParseError>
+    where [Tape<char>; N]: Space<Sym = [char; N], Dir = [Dir; N]> {
+    let def = definition(src, N)?;
+    let array = |v: Vec<char>| -> [char; N] { v.try_into().unwrap() };
+    let map = def.transitions.into_iter()
+        .map(|(mem, sym, mem_new, sym_new, dir)| ((array(sym), mem), (dir.try_into().unwrap(), array(sym_new), mem_new)))
+        .collect();
+    Ok(Turing::new(map, array(def.blank), def.initial, def.accepting, def.rejecting))
+}
+
+pub fn parse_vec(src: &str, tapes: usize) -> Result<Turing<Vec<Tape<char>>, String>, ParseError> {
+    let def = definition(src, tapes)?;
+    let map = def.transitions.into_iter()
+        .map(|(mem, sym, mem_new, sym_new, dir)| ((sym, mem), (dir, sym_new, mem_new)))
+        .collect();
+    Ok(Turing::new(map, def.blank, def.initial, def.accepting, def.rejecting))
+}
+
+fn definition(src: &str, tapes: usize) -> Result<Definition, ParseError> {
+    let mut blank = None;
+    let mut initial = None;
+    let mut accepting = None;
+    let mut rejecting = None;
+    let mut transitions = Vec::new();
+    let mut defined = HashSet::new();
+    let mut lines = 0;
+
+    for (i, text) in src.lines().enumerate() {
+        let line = i + 1;
+        let at = |(column, kind)| ParseError { line, column, kind };
+        lines = line;
+
+        let trimmed = text.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with("//") {
+            continue;
+        }
+
+        let mut line_tokens = tokens(text).map_err(at)?;
+        let end = column(text, text.len());
+        if !line_tokens[0].quoted && line_tokens[0].text.len() > 1 && line_tokens[0].text.ends_with(':') {
+            let head = line_tokens.remove(0);
+            let key = &head.text[..head.text.len() - 1];
+            let slot = match key {
+                "blank" => {
+                    let [token] = exactly(line_tokens, end, ["the blank symbol"]).map_err(at)?;
+                    let sym = symbols(&token, tapes, true).map_err(at)?;
+                    set(&mut blank, sym, key).map_err(|kind| at((head.column, kind)))?;
+                    continue;
+                }
+                "initial" => {
+                    let [token] = exactly(line_tokens, end, ["a state"]).map_err(at)?;
+                    let mem = state(&token).map_err(at)?;
+                    set(&mut initial, mem, key).map_err(|kind| at((head.column, kind)))?;
+                    continue;
+                }
+                "accept" => &mut accepting,
+                "reject" => &mut rejecting,
+                _ => return Err(at((head.column, ParseErrorKind::UnknownKey(key.to_owned())))),
+            };
+            let states = line_tokens.iter().map(state).collect::<Located<Vec<_>>>().map_err(at)?;
+            set(slot, states, key).map_err(|kind| at((head.column, kind)))?;
+            continue;
+        }
+
+        let names = ["a state", "symbols", "`->`", "a state", "symbols", "directions"];
+        let [mem, sym, arrow, mem_new, sym_new, dir] = exactly(line_tokens, end, names).map_err(at)?;
+        if arrow.quoted || arrow.text != "->" {
+            return Err(at((arrow.column, ParseErrorKind::Expected("`->`"))));
+        }
+
+        let key = (state(&mem).map_err(at)?, symbols(&sym, tapes, false).map_err(at)?);
+        let mem_new = state(&mem_new).map_err(at)?;
+        let sym_new = symbols(&sym_new, tapes, false).map_err(at)?;
+        let dir = dirs(&dir, tapes).map_err(at)?;
+        if !defined.insert(key.clone()) {
+            return Err(at((mem.column, ParseErrorKind::DuplicateTransition)));
+        }
+        transitions.push((key.0, key.1, mem_new, sym_new, dir));
+    }
+
+    let missing = |key| ParseError { line: lines + 1, column: 1, kind: ParseErrorKind::MissingKey(key) };
+    Ok(Definition {
+        blank: blank.ok_or_else(|| missing("blank"))?,
+        initial: initial.ok_or_else(|| missing("initial"))?,
+        accepting: accepting.ok_or_else(|| missing("accept"))?,
+        rejecting: rejecting.unwrap_or_default(),
+        transitions,
+    })
+}
+
+type Located<T> = Result<T, (usize, ParseErrorKind)>;
+
+fn column(text: &str, byte: usize) -> usize {
+    text[..byte].chars().count() + 1
+}
+
+fn set<T>(slot: &mut Option<T>, value: T, key: &str) -> Result<(), ParseErrorKind> {
+    if slot.is_some() {
+        return Err(ParseErrorKind::DuplicateKey(key.to_owned()));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+fn tokens(text: &str) -> Located<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    let mut column = 1;
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            column += 1;
+            continue;
+        }
+
+        let token_column = column;
+        if c == '\'' {
+            chars.next();
+            column += 1;
+            let mut escaped = false;
+            let end = loop {
+                match chars.next() {
+                    None => return Err((token_column, ParseErrorKind::UnterminatedQuote)),
+                    Some((_, '\\')) if !escaped => escaped = true,
+                    Some((i, '\'')) if !escaped => break i,
+                    Some(_) => escaped = false,
+                }
+                column += 1;
+            };
+            column += 1;
+            tokens.push(Token { text: &text[start + 1..end], column: token_column, quoted: true });
+        } else {
+            let mut end = text.len();
+            while let Some(&(i, c)) = chars.peek() {
+                if c.is_whitespace() {
+                    end = i;
+                    break;
+                }
+                chars.next();
+                column += 1;
+            }
+            tokens.push(Token { text: &text[start..end], column: token_column, quoted: false });
+        }
+    }
+    Ok(tokens)
+}
+
+fn exactly<'a, const N: usize>(tokens: Vec<Token<'a>>, end: usize, names: [&'static str; N]) -> Located<[Token<'a>; N]> {
+    if tokens.len() > N {
+        let extra = &tokens[N];
+        return Err((extra.column, ParseErrorKind::Unexpected(extra.text.to_owned())));
+    }
+    let found = tokens.len();
+    tokens.try_into().map_err(|_| (end, ParseErrorKind::Expected(names[found])))
+}
+
+fn state(token: &Token) -> Located<String> {
+    if token.quoted {
+        return Err((token.column, ParseErrorKind::Expected("an unquoted state")));
+    }
+    Ok(token.text.to_owned())
+}
+
+fn symbols(token: &Token, tapes: usize, shared: bool) -> Located<Vec<char>> {
+    let mut syms = Vec::new();
+    let mut chars = token.text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if token.quoted => syms.extend(chars.next()),
+            c => syms.push(c),
+        }
+    }
+
+    if shared && syms.len() == 1 {
+        return Ok(vec![syms[0]; tapes]);
+    }
+    if syms.len() != tapes {
+        return Err((token.column, ParseErrorKind::Arity { expected: tapes, found: syms.len() }));
+    }
+    Ok(syms)
+}
+
+fn dirs(token: &Token, tapes: usize) -> Located<Vec<Dir>> {
+    let dirs = token.text.chars().enumerate()
+        .map(|(i, c)| match c {
+            'L' => Ok(Dir::Left),
+            'S' => Ok(Dir::Stay),
+            'R' => Ok(Dir::Right),
+            c => Err((token.column + i, ParseErrorKind::InvalidDir(c))),
+        })
+        .collect::<Located<Vec<_>>>()?;
+
+    if dirs.len() != tapes {
+        return Err((token.column, ParseErrorKind::Arity { expected: tapes, found: dirs.len() }));
+    }
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ parse, parse_array, parse_vec, ParseError, ParseErrorKind };
+    use crate::{ Fuel, Tape };
+
+    const HEADER: &str = "blank: _\ninitial: start\naccept: done\n";
+
+    fn error(src: &str) -> ParseError {
+        parse(src).err().expect("expected an error")
+    }
+
+    #[test]
+    fn parses_the_example() {
+        let src = "// Accepts the words made of a's only.\nblank: _\ninitial: start\naccept: done\nreject: fail\n\n\
+                   start a -> start a R\nstart b -> fail b S\nstart _ -> done _ S\n";
+        let t = parse(src).unwrap();
+        assert!(t.run_with_fuel(Tape::new('_', "aaa".chars()), Fuel::steps(100)).is_accepted());
+        assert!(t.run_with_fuel(Tape::new('_', "aba".chars()), Fuel::steps(100)).is_rejected());
+    }
+
+    #[test]
+    fn parses_quoted_symbols_on_several_tapes() {
+        let src = "blank: _\ninitial: s\naccept: h\ns '_ ' -> h '\\'x' RS\n";
+        let t = parse_array::<2>(src).unwrap();
+        assert_eq!(t.transitions().get(&(['_', ' '], "s".to_owned())).map(|(_, sym, _)| *sym), Some(['\'', 'x']));
+        assert_eq!(parse_vec(src, 2).unwrap().blank(), &vec!['_', '_']);
+    }
+
+    #[test]
+    fn reports_duplicate_keys() {
+        let err = error("blank: _\ninitial: start\n  initial: other\naccept: done\n");
+        assert_eq!((err.line, err.column), (3, 3));
+        assert_eq!(err.kind, ParseErrorKind::DuplicateKey("initial".to_owned()));
+    }
+
+    #[test]
+    fn reports_arity_mismatches() {
+        let err = parse_vec(&format!("{}start ab -> done a RR\n", HEADER), 2).err().unwrap();
+        assert_eq!((err.line, err.column), (4, 18));
+        assert_eq!(err.kind, ParseErrorKind::Arity { expected: 2, found: 1 });
+
+        let err = error(&format!("{}start ab -> done a R\n", HEADER));
+        assert_eq!((err.line, err.column), (4, 7));
+        assert_eq!(err.kind, ParseErrorKind::Arity { expected: 1, found: 2 });
+    }
+
+    #[test]
+    fn reports_bad_directions() {
+        let err = parse_vec(&format!("{}start aa -> done aa RX\n", HEADER), 2).err().unwrap();
+        assert_eq!((err.line, err.column), (4, 22));
+        assert_eq!(err.kind, ParseErrorKind::InvalidDir('X'));
+    }
+
+    #[test]
+    fn reports_unterminated_quotes() {
+        let err = error(&format!("{}\nstart 'a -> done a R\n", HEADER));
+        assert_eq!((err.line, err.column), (5, 7));
+        assert_eq!(err.kind, ParseErrorKind::UnterminatedQuote);
+    }
+
+    #[test]
+    fn reports_missing_keys_after_the_last_line() {
+        let err = error("blank: _\ninitial: start\n\nstart a -> start a R\n");
+        assert_eq!((err.line, err.column), (5, 1));
+        assert_eq!(err.kind, ParseErrorKind::MissingKey("accept"));
+    }
+
+    #[test]
+    fn reports_duplicate_transitions() {
+        let err = error(&format!("{}start a -> done a R\n start a -> start b L\n", HEADER));
+        assert_eq!((err.line, err.column), (5, 2));
+        assert_eq!(err.kind, ParseErrorKind::DuplicateTransition);
+    }
+}
@@ -0,0 +1,250 @@
+use std::hash::Hash;
+use std::ops::Range;
+
+use crate::{ Execution, Fuel, Stats, Step, Tapes };
+
+/// What a step changed on a tape: the cell the head was on, the symbol it held and the cells
+/// the tape covered before the head moved.
+struct Cell<Sym> {
+    head: isize,
+    bounds: Range<isize>,
+    sym: Sym,
+}
+
+struct Undo<Sym, Mem> {
+    mem: Mem,
+    stats: Stats,
+    cells: Vec<Cell<Sym>>,
+}
+
+struct Snapshot<S, Mem> {
+    space: S,
+    mem: Mem,
+    stats: Stats,
+}
+
+/// An execution that can also step backwards.
+///
+/// Each step since the last snapshot is kept in an undo log, and a snapshot of the whole
+/// configuration is taken every `interval` steps, which clears the log. Going back past the log
+/// restores the nearest snapshot before the target and runs forward again. When there are more
+/// than `limit` snapshots every other one is dropped and the interval doubles, so a long run
+/// holds at most `limit` snapshots and an undo log of at most `interval` steps.
+pub struct Rewind<'a, S: Tapes, Mem> {
+    exec: Execution<'a, S, Mem>,
+    undo: Vec<Undo<<S as Tapes>::Cell, Mem>>,
+    snapshots: Vec<Snapshot<S, Mem>>,
+    interval: usize,
+    limit: usize,
+}
+
+impl<'a, S, Mem> Rewind<'a, S, Mem>
+    where S: Tapes + Clone, S::Sym: Hash, S::Cell: Clone, Mem: Clone + Eq + Hash {
+    pub fn new(exec: Execution<'a, S, Mem>) -> Self {
+        Rewind::with_snapshots(exec, 1024, 64)
+    }
+
+    pub fn with_snapshots(exec: Execution<'a, S, Mem>, interval: usize, limit: usize) -> Self {
+        let start = Snapshot { space: exec.space().clone(), mem: exec.state().clone(), stats: exec.stats() };
+        Rewind { exec, undo: Vec::new(), snapshots: vec![start], interval: interval.max(1), limit: limit.max(2) }
+    }
+
+    pub fn execution(&self) -> &Execution<'a, S, Mem> {
+        &self.exec
+    }
+
+    pub fn into_execution(self) -> Execution<'a, S, Mem> {
+        self.exec
+    }
+
+    pub fn state(&self) -> &Mem {
+        self.exec.state()
+    }
+
+    pub fn space(&self) -> &S {
+        self.exec.space()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.exec.stats()
+    }
+
+    /// The number of steps taken when the execution was wrapped, which is as far back as it goes.
+    pub fn first(&self) -> usize {
+        self.snapshots[0].stats.steps
+    }
+
+    pub fn step(&mut self) -> Option<Step<S, Mem>> {
+        let mem = self.exec.state().clone();
+        let stats = self.exec.stats();
+        let before: Vec<(isize, Range<isize>)> = self.exec.space().tapes().iter().map(|tape| (tape.head(), tape.bounds())).collect();
+        let step = self.exec.step()?;
+
+        let cells = before.into_iter().zip(S::syms(&step.read))
+            .map(|((head, bounds), sym)| Cell { head, bounds, sym: sym.clone() })
+            .collect();
+        self.undo.push(Undo { mem, stats, cells });
+        if (self.stats().steps - self.first()).is_multiple_of(self.interval) {
+            self.snapshot();
+        }
+        Some(step)
+    }
+
+    /// Steps forward until the machine halts or `fuel`, counted from the start of the
+    /// execution, runs out.
+    pub fn run(&mut self, fuel: Fuel) {
+        while !fuel.exhausted(&self.stats()) && self.step().is_some() {}
+    }
+
+    /// Undoes the last step. Returns `false` if there is no step to undo.
+    pub fn step_back(&mut self) -> bool {
+        let steps = self.stats().steps;
+        if steps == self.first() {
+            return false;
+        }
+        match self.undo.pop() {
+            Some(undo) => self.apply(undo),
+            None => self.replay(steps - 1),
+        }
+        true
+    }
+
+    /// Goes forward or back to the configuration after `steps` steps. Returns `false` if the
+    /// machine halts or `fuel` runs out before getting there, or if it is before the start of the
+    /// execution, in which case it stops at the closest configuration.
+    pub fn seek(&mut self, steps: usize, fuel: Fuel) -> bool {
+        while self.stats().steps < steps && !fuel.exhausted(&self.stats()) && self.step().is_some() {}
+        let target = steps.max(self.first());
+        let current = self.stats().steps;
+        if target < current {
+            if current - target <= self.undo.len() {
+                for _ in target..current {
+                    let undo = self.undo.pop().unwrap();
+                    self.apply(undo);
+                }
+            } else {
+                self.replay(target);
+            }
+        }
+        self.stats().steps == steps
+    }
+
+    /// Steps back until the machine is in `mem`, taking at least one step. Returns `false` and
+    /// stops at the start if it never was.
+    pub fn back_to(&mut self, mem: &Mem) -> bool {
+        while self.step_back() {
+            if self.state() == mem {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn apply(&mut self, undo: Undo<S::Cell, Mem>) {
+        let (space, mem, stats) = self.exec.parts_mut();
+        for (tape, cell) in space.tapes_mut().iter_mut().zip(undo.cells) {
+            tape.rewind(cell.head, cell.bounds, cell.sym);
+        }
+        *mem = undo.mem;
+        *stats = undo.stats;
+    }
+
+    /// Restores the last snapshot at or before `steps` and runs forward to it, dropping what
+    /// came after.
+    fn replay(&mut self, steps: usize) {
+        let i = self.snapshots.iter().rposition(|snapshot| snapshot.stats.steps <= steps).unwrap_or(0);
+        self.snapshots.truncate(i + 1);
+        let Snapshot { space, mem, stats } = &self.snapshots[i];
+        self.exec.restore(space.clone(), mem.clone(), *stats);
+        self.undo.clear();
+        while self.stats().steps < steps && self.step().is_some() {}
+    }
+
+    fn snapshot(&mut self) {
+        self.undo.clear();
+        self.snapshots.push(Snapshot { space: self.exec.space().clone(), mem: self.exec.state().clone(), stats: self.exec.stats() });
+        if self.snapshots.len() > self.limit {
+            let (first, interval) = (self.first(), 2 * self.interval);
+            self.snapshots.retain(|snapshot| (snapshot.stats.steps - first).is_multiple_of(interval));
+            self.interval = interval;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rewind;
+    use crate::{ Fuel, Tape, Turing };
+    use crate::fixtures::beaver;
+
+    fn assert_at(t: &Turing<Tape<u8>, char>, rewind: &Rewind<'_, Tape<u8>, char>, steps: usize) {
+        let halt = t.run_with_limit(Tape::new(0, None), steps);
+        assert_eq!(rewind.stats(), halt.stats());
+        assert_eq!(rewind.state(), halt.mem());
+        assert_eq!(rewind.space(), halt.space());
+        assert_eq!(rewind.space().head(), halt.space().head());
+        assert_eq!(rewind.space().bounds(), halt.space().bounds());
+    }
+
+    #[test]
+    fn steps_back_through_snapshots() {
+        let t = beaver();
+        let mut rewind = Rewind::with_snapshots(t.start(Tape::new(0, None)), 3, 4);
+        rewind.run(Fuel::unlimited());
+        assert_at(&t, &rewind, 107);
+        for steps in (0..107).rev() {
+            assert!(rewind.step_back());
+            assert_at(&t, &rewind, steps);
+        }
+        assert!(!rewind.step_back());
+        assert_at(&t, &rewind, 0);
+    }
+
+    #[test]
+    fn seeks_across_snapshots() {
+        let t = beaver();
+        let mut rewind = Rewind::with_snapshots(t.start(Tape::new(0, None)), 2, 3);
+        for target in [100, 5, 60, 0, 107, 33, 34, 2, 80, 79, 1] {
+            assert!(rewind.seek(target, Fuel::unlimited()));
+            assert_at(&t, &rewind, target);
+        }
+        assert!(!rewind.seek(200, Fuel::unlimited()));
+        assert_at(&t, &rewind, 107);
+    }
+
+    #[test]
+    fn seeks_no_further_than_the_fuel() {
+        let t = beaver();
+        let mut rewind = Rewind::with_snapshots(t.start(Tape::new(0, None)), 2, 3);
+        assert!(!rewind.seek(80, Fuel::steps(50)));
+        assert_at(&t, &rewind, 50);
+        assert!(rewind.seek(20, Fuel::steps(50)));
+        assert_at(&t, &rewind, 20);
+    }
+
+    #[test]
+    fn thins_snapshots_past_the_limit() {
+        let t = beaver();
+        let mut rewind = Rewind::with_snapshots(t.start(Tape::new(0, None)), 1, 3);
+        rewind.run(Fuel::unlimited());
+        assert!(rewind.snapshots.len() <= 3);
+        assert!(rewind.interval >= 64);
+        assert!(rewind.snapshots.iter().all(|snapshot| snapshot.stats.steps % rewind.interval == 0));
+        assert!(rewind.undo.len() < rewind.interval);
+        assert!(rewind.seek(3, Fuel::unlimited()));
+        assert_at(&t, &rewind, 3);
+    }
+
+    #[test]
+    fn goes_back_to_a_state() {
+        let t = beaver();
+        let mut rewind = Rewind::with_snapshots(t.start(Tape::new(0, None)), 4, 4);
+        rewind.run(Fuel::steps(50));
+        assert!(rewind.back_to(&'C'));
+        let steps = rewind.stats().steps;
+        assert_at(&t, &rewind, steps);
+        assert!((steps + 1..50).all(|n| t.run_with_limit(Tape::new(0, None), n).mem() != &'C'));
+        assert!(!rewind.back_to(&'H'));
+        assert_at(&t, &rewind, 0);
+    }
+}
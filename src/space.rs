@@ -5,6 +5,14 @@ pub trait Space {
     fn read(&self) -> Self::Sym;
     fn write(&mut self, sym: Self::Sym);
     fn mov(&mut self, dir: &Self::Dir, def: &Self::Sym);
+    /// The number of cells the space holds, which `Fuel::cells` limits. Spaces that do not
+    /// count their cells hold none, so a cell limit never stops them.
+    fn size(&self) -> usize {
+        0
+    }
+    /// Drops cells that make no difference to the configuration, so equal configurations
+    /// compare equal. Spaces without such cells are left as they are.
+    fn normalize(&mut self, _def: &Self::Sym) {}
 }
 
 
@@ -25,6 +33,14 @@ macro_rules! impl_tuple_space {
             fn mov(&mut self, dir: &Self::Dir, def: &Self::Sym) {
                 $(self.$n.mov(&dir.$n, &def.$n);)+
             }
+
+            fn size(&self) -> usize {
+                [$(self.$n.size()),+].iter().sum()
+            }
+
+            fn normalize(&mut self, def: &Self::Sym) {
+                $(self.$n.normalize(&def.$n);)+
+            }
         }
     };
 }
@@ -46,6 +62,14 @@ macro_rules! impl_array_space {
             fn mov(&mut self, dir: &Self::Dir, def: &Self::Sym) {
                 $(self[$n - $i - 1].mov(&dir[$n - $i - 1], &def[$n - $i - 1]);)+
             }
+
+            fn size(&self) -> usize {
+                self.iter().map(|s|s.size()).sum()
+            }
+
+            fn normalize(&mut self, def: &Self::Sym) {
+                $(self[$n - $i - 1].normalize(&def[$n - $i - 1]);)+
+            }
         }
 
         impl_array_space!($($i),+);
@@ -88,4 +112,12 @@ impl<T: Space> Space for Vec<T> {
     fn mov(&mut self, dir: &Self::Dir, def: &Self::Sym) {
         self.iter_mut().zip(dir).zip(def).for_each(|((t,d),s)|t.mov(d,s));
     }
+
+    fn size(&self) -> usize {
+        self.iter().map(|s|s.size()).sum()
+    }
+
+    fn normalize(&mut self, def: &Self::Sym) {
+        self.iter_mut().zip(def).for_each(|(t,s)|t.normalize(s));
+    }
 }
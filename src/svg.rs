@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::hash::Hash;
+use std::io::{ self, Write };
+
+use crate::{ Execution, Fuel, Halt, Label, Tapes, Turing };
+
+const PALETTE: [&str; 12] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948",
+    "#b07aa1", "#ff9da7", "#9c755f", "#bab0ac", "#86bcb6", "#d37295",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvgOptions {
+    /// Only every `every`-th step is drawn. The final configuration is always drawn.
+    pub every: usize,
+    /// Only draws the cells the head visited, rather than every cell of the tape.
+    pub crop: bool,
+    /// The width and height of a cell in pixels.
+    pub cell: usize,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions { every: 1, crop: false, cell: 8 }
+    }
+}
+
+struct Row<Sym> {
+    start: isize,
+    cells: Vec<Sym>,
+    head: isize,
+}
+
+/// A space-time diagram of a run: each drawn step is a row of cells, one panel per tape.
+pub struct SpaceTime<Sym> {
+    options: SvgOptions,
+    blanks: Vec<Sym>,
+    panels: Vec<Vec<Row<Sym>>>,
+    visited: Vec<(isize, isize)>,
+    last: Option<usize>,
+}
+
+impl<Sym: Clone> SpaceTime<Sym> {
+    /// An empty diagram of tapes with the given blanks, which are drawn white.
+    pub fn new(options: SvgOptions, blanks: &[Sym]) -> Self {
+        let options = SvgOptions { every: options.every.max(1), ..options };
+        SpaceTime { options, blanks: blanks.to_vec(), panels: Vec::new(), visited: Vec::new(), last: None }
+    }
+
+    /// Records the space after `step` steps. Call this after every step so the visited region is
+    /// known, even for steps that are not drawn.
+    pub fn record<S: Tapes<Cell = Sym>>(&mut self, step: usize, space: &S) {
+        self.visit(space);
+        if step.is_multiple_of(self.options.every) {
+            self.sample(step, space);
+        }
+    }
+
+    /// Records the final space, which is drawn even if it falls between two sampled steps.
+    pub fn finish<S: Tapes<Cell = Sym>>(&mut self, step: usize, space: &S) {
+        self.visit(space);
+        if self.last != Some(step) {
+            self.sample(step, space);
+        }
+    }
+
+    fn visit<S: Tapes<Cell = Sym>>(&mut self, space: &S) {
+        let tapes = space.tapes();
+        if self.visited.is_empty() {
+            self.visited = tapes.iter().map(|t| (t.head(), t.head())).collect();
+            self.panels = tapes.iter().map(|_| Vec::new()).collect();
+        }
+        for (visited, tape) in self.visited.iter_mut().zip(tapes) {
+            visited.0 = visited.0.min(tape.head());
+            visited.1 = visited.1.max(tape.head());
+        }
+    }
+
+    fn sample<S: Tapes<Cell = Sym>>(&mut self, step: usize, space: &S) {
+        self.last = Some(step);
+        for (panel, tape) in self.panels.iter_mut().zip(space.tapes()) {
+            panel.push(Row { start: tape.bounds().start, cells: tape.cells().cloned().collect(), head: tape.head() });
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.panels.first().map_or(0, Vec::len)
+    }
+}
+
+impl<Sym: Clone + Eq + Hash + Label> SpaceTime<Sym> {
+    pub fn to_svg(&self) -> String {
+        let c = self.options.cell;
+        let gap = 2 * c;
+        let mut colors: HashMap<&Sym, String> = HashMap::new();
+        let mut legend = Vec::new();
+        let columns: Vec<(isize, isize)> = self.panels.iter().zip(&self.visited)
+            .map(|(panel, &visited)| if self.options.crop {
+                visited
+            } else {
+                panel.iter().fold(visited, |(lo, hi), row| (lo.min(row.start), hi.max(row.start + row.cells.len() as isize - 1)))
+            })
+            .collect();
+
+        let width = columns.iter().map(|(lo, hi)| (hi - lo + 1) as usize).max().unwrap_or(0) * c;
+        let mut body = String::new();
+        let mut y = 0;
+        for (i, (panel, &(lo, hi))) in self.panels.iter().zip(&columns).enumerate() {
+            let blank = self.blanks.get(i);
+            for row in panel {
+                let mut run: Option<(isize, &Sym)> = None;
+                let cells = (lo..=hi + 1).map(|i| {
+                    let j = i - row.start;
+                    if i > hi || j < 0 || j >= row.cells.len() as isize { None } else { Some(&row.cells[j as usize]) }
+                });
+                for (i, sym) in (lo..=hi + 1).zip(cells) {
+                    let sym = sym.filter(|&sym| Some(sym) != blank);
+                    if run.map(|(_, s)| Some(s)) == Some(sym) {
+                        continue;
+                    }
+                    if let Some((start, sym)) = run.take() {
+                        let next = colors.len();
+                        let fill = colors.entry(sym).or_insert_with(|| {
+                            legend.push(sym);
+                            color(next)
+                        });
+                        writeln!(body, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                            (start - lo) as usize * c, y, (i - start) as usize * c, c, fill).unwrap();
+                    }
+                    run = sym.map(|sym| (i, sym));
+                }
+
+                if lo <= row.head && row.head <= hi {
+                    let stroke = (c / 5).max(1);
+                    writeln!(body, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="black" stroke-width="{}"/>"#,
+                        (row.head - lo) as usize * c + stroke / 2, y + stroke / 2, c - stroke, c - stroke, stroke).unwrap();
+                }
+                y += c;
+            }
+            y += gap;
+        }
+
+        for (i, sym) in legend.iter().enumerate() {
+            writeln!(body, r#"<rect x="0" y="{}" width="{}" height="{}" fill="{}"/>"#, y + i * (c + 4), c, c, colors[sym]).unwrap();
+            writeln!(body, r#"<text x="{}" y="{}" font-family="monospace" font-size="{}">{}</text>"#,
+                c + 4, y + i * (c + 4) + c, c.max(10), escape(&sym.label())).unwrap();
+        }
+        let height = y + legend.len() * (c + 4);
+        let width = width.max(c + 60);
+
+        format!(concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#, "\n",
+            r#"<rect width="{0}" height="{1}" fill="white"/>"#, "\n",
+            "{2}</svg>\n"), width, height, body)
+    }
+
+    pub fn write_svg<W: Write>(&self, mut out: W) -> io::Result<()> {
+        out.write_all(self.to_svg().as_bytes())
+    }
+}
+
+fn color(i: usize) -> String {
+    match PALETTE.get(i) {
+        Some(color) => (*color).to_owned(),
+        None => format!("hsl({}, 60%, 55%)", (i * 137) % 360),
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+impl<S, Mem> Turing<S, Mem>
+    where S: Tapes, S::Sym: Hash, S::Cell: Clone, Mem: Clone + Eq + Hash {
+    /// Runs the machine like `run_with_fuel`, recording a space-time diagram along the way.
+    pub fn space_time(&self, space: S, fuel: Fuel, options: SvgOptions) -> (Halt<S, Mem>, SpaceTime<S::Cell>) {
+        let mut diagram = SpaceTime::new(options, S::syms(&self.default));
+        let mut exec = Execution::new(self, space);
+        diagram.record(0, exec.space());
+        while !fuel.exhausted(&exec.stats()) && exec.step().is_some() {
+            diagram.record(exec.stats().steps, exec.space());
+        }
+        diagram.finish(exec.stats().steps, exec.space());
+        (exec.into_halt(), diagram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SvgOptions;
+    use crate::{ Fuel, Tape, Turing, turing, Dir::* };
+
+    /// Writes `1`s going right until it reaches the `|`.
+    fn writer() -> Turing<Tape<char>, char> {
+        turing!('_';'a';'h';
+            ('a') { '_' => (Right, '1', 'a') '|' => (Stay, '|', 'h') },
+        )
+    }
+
+    fn input() -> Tape<char> {
+        Tape::new('_', "_________|".chars())
+    }
+
+    #[test]
+    fn samples_every_few_steps_and_the_last() {
+        let options = |every| SvgOptions { every, ..SvgOptions::default() };
+        let (halt, diagram) = writer().space_time(input(), Fuel::unlimited(), options(3));
+        assert_eq!(halt.stats().steps, 10);
+        assert_eq!(diagram.rows(), 5);
+        let last = diagram.panels[0].last().unwrap();
+        assert_eq!(last.cells, halt.space().cells().cloned().collect::<Vec<_>>());
+        assert_eq!(last.head, 9);
+
+        assert_eq!(writer().space_time(input(), Fuel::unlimited(), options(5)).1.rows(), 3);
+        assert_eq!(writer().space_time(input(), Fuel::unlimited(), options(0)).1.rows(), 11);
+        assert_eq!(writer().space_time(input(), Fuel::steps(4), options(3)).1.rows(), 3);
+    }
+
+    #[test]
+    fn crops_to_the_visited_cells() {
+        let tape = Tape::new('_', "___________________|".chars());
+        let options = SvgOptions { cell: 20, ..SvgOptions::default() };
+        let (_, diagram) = writer().space_time(tape.clone(), Fuel::steps(3), options);
+        assert!(diagram.to_svg().starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="400" "#));
+        let (_, diagram) = writer().space_time(tape, Fuel::steps(3), SvgOptions { crop: true, ..options });
+        assert!(diagram.to_svg().starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="80" "#));
+    }
+
+    #[test]
+    fn stacks_a_panel_per_tape() {
+        let t: Turing<[Tape<char>; 2], char> = turing!(['_', '_'];'a';'h';
+            ('a') { ['_', '_'] => ([Right, Left], ['1', '2'], 'b') },
+            ('b') { ['_', '_'] => ([Stay, Stay], ['1', '2'], 'h') },
+        );
+        let options = SvgOptions { cell: 10, ..SvgOptions::default() };
+        let (_, diagram) = t.space_time([Tape::new('_', None), Tape::new('_', None)], Fuel::unlimited(), options);
+        assert_eq!(diagram.panels.len(), 2);
+        assert_eq!(diagram.rows(), 3);
+
+        // Three rows of 10 pixels and a 20 pixel gap per panel, then a line per symbol.
+        let svg = diagram.to_svg();
+        let heads = |y| format!(r#"y="{}" width="8" height="8" fill="none" stroke="black""#, y);
+        for y in [1, 11, 21, 51, 61, 71] {
+            assert!(svg.contains(&heads(y)), "no head at {} in {}", y, svg);
+        }
+        assert!(svg.contains(r#"height="128" viewBox="0 0 70 128""#), "{}", svg);
+    }
+}
@@ -1,23 +1,307 @@
 use std::collections::VecDeque;
-use std::fmt::{ self, Display, Formatter };
+use std::collections::vec_deque::Iter;
+use std::fmt::{ self, Debug, Display, Formatter };
+use std::hash::{ Hash, Hasher };
 use std::iter::FromIterator;
+use std::ops::Range;
+use std::str::FromStr;
 
-use crate::{ Dir, Space };
+use crate::{ Dir, ParseError, ParseErrorKind, Space };
 
+/// A tape that grows with blanks in both directions as the head moves.
+///
+/// Cells are indexed relative to the first cell the tape was created with, so cells added to
+/// the left have negative indices. Two tapes are equal when their cells and head position are,
+/// regardless of how they were indexed. Use `eq_normalized` or `normalize` to also ignore the
+/// blanks around the content.
+#[derive(Clone)]
 pub struct Tape<Sym> {
     tape: VecDeque<Sym>,
     pos: usize,
+    origin: isize,
 }
 
+/// Collects the cells with the head on the first one. Collecting nothing gives a tape without
+/// cells, which panics when read, so prefer `Tape::new` when the input may be empty.
 impl<Sym> FromIterator<Sym> for Tape<Sym> {
     fn from_iter<I: IntoIterator<Item = Sym>>(iter: I) -> Self {
         Tape {
             tape: iter.into_iter().collect(),
-            pos: 0
+            pos: 0,
+            origin: 0,
         }
     }
 }
 
+impl<Sym> Tape<Sym> {
+    /// A tape holding `input`, with the head on its first cell or on a blank if it is empty.
+    pub fn new<I: IntoIterator<Item = Sym>>(blank: Sym, input: I) -> Self
+        where Sym: Clone {
+        Tape::with_head(blank, 0, input)
+    }
+
+    /// A tape holding `input`, with the head on cell `head`, where the input starts at cell 0.
+    /// The tape is padded with blanks to reach the head.
+    pub fn with_head<I: IntoIterator<Item = Sym>>(blank: Sym, head: isize, input: I) -> Self
+        where Sym: Clone {
+        let mut tape: VecDeque<Sym> = input.into_iter().collect();
+        let origin = (-head).max(0);
+        for _ in 0..origin {
+            tape.push_front(blank.clone());
+        }
+
+        let pos = (head + origin) as usize;
+        if pos >= tape.len() {
+            tape.resize(pos + 1, blank);
+        }
+        Tape { tape, pos, origin }
+    }
+
+    /// The index of the cell under the head.
+    pub fn head(&self) -> isize {
+        self.pos as isize - self.origin
+    }
+
+    /// The indices of the cells the tape holds.
+    pub fn bounds(&self) -> Range<isize> {
+        -self.origin..self.tape.len() as isize - self.origin
+    }
+
+    pub fn get(&self, index: isize) -> Option<&Sym> {
+        let i = index + self.origin;
+        if i < 0 {
+            return None;
+        }
+        self.tape.get(i as usize)
+    }
+
+    pub fn cells(&self) -> Iter<'_, Sym> {
+        self.tape.iter()
+    }
+
+    /// The cells at most `radius` cells from the head.
+    pub fn window(&self, radius: usize) -> Iter<'_, Sym> {
+        let start = self.pos.saturating_sub(radius);
+        let end = (self.pos + radius + 1).min(self.tape.len());
+        self.tape.range(start..end)
+    }
+
+    /// The cells from the first to the last one that is not `blank`.
+    pub fn trimmed(&self, blank: &Sym) -> Iter<'_, Sym>
+        where Sym: PartialEq {
+        let start = self.tape.iter().position(|sym| sym != blank).unwrap_or(self.tape.len());
+        let end = self.tape.iter().rposition(|sym| sym != blank).map_or(start, |i| i + 1);
+        self.tape.range(start..end)
+    }
+
+    /// The index of the first cell returned by `trimmed`.
+    pub fn trimmed_start(&self, blank: &Sym) -> isize
+        where Sym: PartialEq {
+        let start = self.tape.iter().position(|sym| sym != blank).unwrap_or(self.tape.len());
+        start as isize - self.origin
+    }
+
+    /// Whether the tapes hold the same symbols with the head on the same one, ignoring blanks
+    /// before and after the content and how the cells are indexed.
+    pub fn eq_normalized(&self, other: &Self, blank: &Sym) -> bool
+        where Sym: PartialEq {
+        let (start, end) = self.content(blank);
+        let (other_start, other_end) = other.content(blank);
+        self.pos - start == other.pos - other_start
+            && self.tape.range(start..end).eq(other.tape.range(other_start..other_end))
+    }
+
+    /// Removes blanks before and after the content, keeping the cell under the head.
+    pub fn normalize(&mut self, blank: &Sym)
+        where Sym: PartialEq {
+        let (start, end) = self.content(blank);
+        self.tape.truncate(end);
+        self.tape.drain(..start);
+        self.pos -= start;
+        self.origin -= start as isize;
+    }
+
+    pub fn normalized(mut self, blank: &Sym) -> Self
+        where Sym: PartialEq {
+        self.normalize(blank);
+        self
+    }
+
+    fn content(&self, blank: &Sym) -> (usize, usize)
+        where Sym: PartialEq {
+        let start = self.tape.iter().position(|sym| sym != blank).unwrap_or(self.pos).min(self.pos);
+        let end = self.tape.iter().rposition(|sym| sym != blank).unwrap_or(self.pos).max(self.pos) + 1;
+        (start, end)
+    }
+
+    /// Undoes a step that wrote on the cell at `head` and moved, putting back `sym` and dropping
+    /// the cells the tape grew by since it covered `bounds`.
+    pub(crate) fn rewind(&mut self, head: isize, bounds: Range<isize>, sym: Sym) {
+        let grown = (bounds.start - self.bounds().start) as usize;
+        self.tape.drain(..grown);
+        self.tape.truncate((bounds.end - bounds.start) as usize);
+        self.origin = -bounds.start;
+        self.pos = (head - bounds.start) as usize;
+        self.tape[self.pos] = sym;
+    }
+
+    pub(crate) fn from_parts(tape: VecDeque<Sym>, pos: usize, origin: isize) -> Self {
+        Tape { tape, pos, origin }
+    }
+
+    pub(crate) fn into_parts(self) -> (VecDeque<Sym>, usize, isize) {
+        (self.tape, self.pos, self.origin)
+    }
+
+    pub fn into_vec(self) -> Vec<Sym> {
+        self.tape.into()
+    }
+}
+
+impl<Sym> From<Tape<Sym>> for Vec<Sym> {
+    fn from(tape: Tape<Sym>) -> Self {
+        tape.into_vec()
+    }
+}
+
+impl From<Tape<char>> for String {
+    fn from(tape: Tape<char>) -> Self {
+        tape.tape.into_iter().collect()
+    }
+}
+
+/// Parses a configuration such as `ab[c]d`, where the brackets mark the cell under the head and
+/// the first character is cell 0.
+impl FromStr for Tape<char> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let error = |column, kind| ParseError { line: 1, column, kind };
+        let mut tape = VecDeque::new();
+        let mut pos = None;
+        let mut chars = s.chars().enumerate();
+        while let Some((i, c)) = chars.next() {
+            if c != '[' {
+                if c == ']' {
+                    return Err(error(i + 1, ParseErrorKind::Unexpected(c.to_string())));
+                }
+                tape.push_back(c);
+                continue;
+            }
+
+            if pos.is_some() {
+                return Err(error(i + 1, ParseErrorKind::Unexpected(c.to_string())));
+            }
+            match (chars.next(), chars.next()) {
+                (Some((_, c)), Some((_, ']'))) if c != ']' => {
+                    pos = Some(tape.len());
+                    tape.push_back(c);
+                }
+                (_, _) => return Err(error(i + 1, ParseErrorKind::Expected("a single symbol in brackets"))),
+            }
+        }
+
+        let pos = pos.ok_or_else(|| error(s.chars().count() + 1, ParseErrorKind::Expected("a head marked with `[` and `]`")))?;
+        Ok(Tape { tape, pos, origin: 0 })
+    }
+}
+
+impl<Sym: Debug> Debug for Tape<Sym> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Tape")
+            .field("cells", &self.tape)
+            .field("head", &self.head())
+            .finish()
+    }
+}
+
+impl<Sym: PartialEq> PartialEq for Tape<Sym> {
+    fn eq(&self, other: &Self) -> bool {
+        self.pos == other.pos && self.tape == other.tape
+    }
+}
+
+impl<Sym: Eq> Eq for Tape<Sym> {}
+
+impl<Sym: Hash> Hash for Tape<Sym> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pos.hash(state);
+        self.tape.hash(state);
+    }
+}
+
+/// A space made of tapes with the same kind of symbols, such as `Tape`, `[Tape; N]` or
+/// `Vec<Tape>`, whose symbols and directions hold one for each tape.
+pub trait Tapes: Space {
+    type Cell;
+
+    fn tapes(&self) -> &[Tape<Self::Cell>];
+    fn tapes_mut(&mut self) -> &mut [Tape<Self::Cell>];
+    fn syms(sym: &Self::Sym) -> &[Self::Cell];
+    fn dirs(dir: &Self::Dir) -> &[Dir];
+}
+
+impl<Sym: Clone + Eq> Tapes for Tape<Sym> {
+    type Cell = Sym;
+
+    fn tapes(&self) -> &[Tape<Sym>] {
+        std::slice::from_ref(self)
+    }
+
+    fn tapes_mut(&mut self) -> &mut [Tape<Sym>] {
+        std::slice::from_mut(self)
+    }
+
+    fn syms(sym: &Sym) -> &[Sym] {
+        std::slice::from_ref(sym)
+    }
+
+    fn dirs(dir: &Dir) -> &[Dir] {
+        std::slice::from_ref(dir)
+    }
+}
+
+impl<Sym: Clone + Eq, const N: usize> Tapes for [Tape<Sym>; N]
+    where [Tape<Sym>; N]: Space<Sym = [Sym; N], Dir = [Dir; N]> {
+    type Cell = Sym;
+
+    fn tapes(&self) -> &[Tape<Sym>] {
+        self
+    }
+
+    fn tapes_mut(&mut self) -> &mut [Tape<Sym>] {
+        self
+    }
+
+    fn syms(sym: &[Sym; N]) -> &[Sym] {
+        sym
+    }
+
+    fn dirs(dir: &[Dir; N]) -> &[Dir] {
+        dir
+    }
+}
+
+impl<Sym: Clone + Eq> Tapes for Vec<Tape<Sym>> {
+    type Cell = Sym;
+
+    fn tapes(&self) -> &[Tape<Sym>] {
+        self
+    }
+
+    fn tapes_mut(&mut self) -> &mut [Tape<Sym>] {
+        self
+    }
+
+    fn syms(sym: &Vec<Sym>) -> &[Sym] {
+        sym
+    }
+
+    fn dirs(dir: &Vec<Dir>) -> &[Dir] {
+        dir
+    }
+}
+
 impl<Sym: Clone + Eq> Space for Tape<Sym> {
     type Sym = Sym;
     type Dir = Dir;
@@ -32,7 +316,10 @@ impl<Sym: Clone + Eq> Space for Tape<Sym> {
 
     fn mov(&mut self, dir: &Dir, def: &Sym) {
         match dir {
-            Dir::Left if self.pos == 0 => self.tape.push_front(def.clone()),
+            Dir::Left if self.pos == 0 => {
+                self.tape.push_front(def.clone());
+                self.origin += 1;
+            }
             Dir::Left => self.pos -= 1,
             Dir::Stay => (),
             Dir::Right => {
@@ -43,6 +330,14 @@ impl<Sym: Clone + Eq> Space for Tape<Sym> {
             }
         }
     }
+
+    fn size(&self) -> usize {
+        self.tape.len()
+    }
+
+    fn normalize(&mut self, def: &Sym) {
+        Tape::normalize(self, def)
+    }
 }
 
 impl<Sym: Display> Display for Tape<Sym> {
@@ -51,7 +346,157 @@ impl<Sym: Display> Display for Tape<Sym> {
             write!(f, "{}", sym)?;
         }
 
-        writeln!(f, "")?;
+        writeln!(f)?;
         write!(f, "{:>1$}", "^", self.pos+1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{ Hash, Hasher };
+
+    use super::Tape;
+    use crate::{ ParseError, ParseErrorKind };
+
+    fn hash(tape: &Tape<char>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        tape.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn equality_ignores_indexing() {
+        let shifted = Tape::with_head('_', -2, "ab".chars());
+        let fresh = Tape::new('_', "__ab".chars());
+        assert_ne!(shifted.bounds(), fresh.bounds());
+        assert_eq!(shifted, fresh);
+        assert_eq!(hash(&shifted), hash(&fresh));
+        assert_ne!(shifted, Tape::with_head('_', 1, "__ab".chars()));
+        assert_ne!(shifted, Tape::new('_', "__ab_".chars()));
+    }
+
+    #[test]
+    fn eq_normalized_ignores_surrounding_blanks() {
+        let tape = Tape::new('_', "ab".chars());
+        assert!(tape.eq_normalized(&Tape::with_head('_', 2, "__ab__".chars()), &'_'));
+        assert!(Tape::with_head('_', 2, "__ab__".chars()).eq_normalized(&tape, &'_'));
+        assert!(!tape.eq_normalized(&Tape::with_head('_', 3, "__ab__".chars()), &'_'));
+        assert!(!tape.eq_normalized(&Tape::new('_', "a_b".chars()), &'_'));
+    }
+
+    #[test]
+    fn eq_normalized_keeps_blanks_up_to_the_head() {
+        let right = Tape::with_head('_', 4, "ab".chars());
+        assert!(right.eq_normalized(&Tape::with_head('_', 6, "__ab".chars()), &'_'));
+        assert!(!right.eq_normalized(&Tape::with_head('_', 3, "ab".chars()), &'_'));
+
+        let left = Tape::with_head('_', -2, "ab".chars());
+        assert!(left.eq_normalized(&Tape::with_head('_', 1, "___ab".chars()), &'_'));
+        assert!(!left.eq_normalized(&Tape::with_head('_', 0, "___ab".chars()), &'_'));
+    }
+
+    #[test]
+    fn eq_normalized_on_blank_tapes() {
+        let blank = Tape::new('_', None);
+        assert!(blank.eq_normalized(&Tape::with_head('_', 3, "_____".chars()), &'_'));
+        assert!(blank.eq_normalized(&Tape::with_head('_', -4, None), &'_'));
+        assert!(!blank.eq_normalized(&Tape::new('_', "1".chars()), &'_'));
+    }
+
+    #[test]
+    fn normalize_keeps_cell_indices() {
+        let tape = Tape::with_head('_', 5, "__ab__".chars());
+        let normalized = tape.clone().normalized(&'_');
+        assert_eq!(normalized.head(), 5);
+        assert_eq!(normalized.bounds(), 2..6);
+        assert_eq!(normalized.get(2), Some(&'a'));
+        assert_eq!(normalized.cells().collect::<String>(), "ab__");
+        assert!(normalized.eq_normalized(&tape, &'_'));
+
+        let blank = Tape::with_head('_', -3, "__".chars()).normalized(&'_');
+        assert_eq!(blank.head(), -3);
+        assert_eq!(blank.bounds(), -3..-2);
+    }
+
+    #[test]
+    fn pads_the_input_to_reach_the_head() {
+        let left = Tape::with_head('_', -2, "ab".chars());
+        assert_eq!((left.head(), left.bounds()), (-2, -2..2));
+        assert_eq!(left.cells().collect::<String>(), "__ab");
+        assert_eq!(left.get(0), Some(&'a'));
+
+        let right = Tape::with_head('_', 4, "ab".chars());
+        assert_eq!((right.head(), right.bounds()), (4, 0..5));
+        assert_eq!(right.cells().collect::<String>(), "ab___");
+
+        let inside = Tape::with_head('_', 1, "ab".chars());
+        assert_eq!((inside.head(), inside.bounds()), (1, 0..2));
+    }
+
+    #[test]
+    fn starts_on_a_blank_without_input() {
+        let blank = Tape::new('_', None);
+        assert_eq!((blank.head(), blank.bounds()), (0, 0..1));
+        assert_eq!(blank.get(0), Some(&'_'));
+
+        let left = Tape::with_head('_', -1, None);
+        assert_eq!((left.head(), left.bounds()), (-1, -1..0));
+        assert_eq!(left.get(-1), Some(&'_'));
+    }
+
+    #[test]
+    fn parses_configurations() {
+        let tape: Tape<char> = "ab[c]d".parse().unwrap();
+        assert_eq!((tape.head(), tape.bounds()), (2, 0..4));
+        assert_eq!(tape.cells().collect::<String>(), "abcd");
+        assert_eq!(tape, Tape::with_head('_', 2, "abcd".chars()));
+
+        let tape: Tape<char> = "[_]".parse().unwrap();
+        assert_eq!(tape, Tape::new('_', None));
+    }
+
+    #[test]
+    fn reports_where_configurations_are_malformed() {
+        let error = |s: &str| s.parse::<Tape<char>>().unwrap_err();
+        let brackets = ParseErrorKind::Expected("a single symbol in brackets");
+        assert_eq!(error("[]"), ParseError { line: 1, column: 1, kind: brackets.clone() });
+        assert_eq!(error("[ab]"), ParseError { line: 1, column: 1, kind: brackets.clone() });
+        assert_eq!(error("ab[c"), ParseError { line: 1, column: 3, kind: brackets });
+        assert_eq!(error("a]"), ParseError { line: 1, column: 2, kind: ParseErrorKind::Unexpected("]".to_owned()) });
+        assert_eq!(error("[a][b]"), ParseError { line: 1, column: 4, kind: ParseErrorKind::Unexpected("[".to_owned()) });
+        assert_eq!(error("abc"), ParseError { line: 1, column: 4, kind: ParseErrorKind::Expected("a head marked with `[` and `]`") });
+    }
+
+    #[test]
+    fn reads_cells_around_the_head() {
+        let tape = Tape::with_head('_', 1, "abcde".chars());
+        assert_eq!(tape.head(), 1);
+        assert_eq!(tape.cells().collect::<String>(), "abcde");
+        assert_eq!(tape.window(0).collect::<String>(), "b");
+        assert_eq!(tape.window(1).collect::<String>(), "abc");
+        assert_eq!(tape.window(2).collect::<String>(), "abcd");
+        assert_eq!(Tape::with_head('_', 3, "abcde".chars()).window(2).collect::<String>(), "bcde");
+        assert_eq!(tape.window(10).collect::<String>(), "abcde");
+    }
+
+    #[test]
+    fn trims_blanks_around_the_content() {
+        let tape = Tape::with_head('_', -1, "_ab_c__".chars());
+        assert_eq!(tape.trimmed(&'_').collect::<String>(), "ab_c");
+        assert_eq!(tape.trimmed_start(&'_'), 1);
+        assert_eq!(tape.trimmed(&'a').collect::<String>(), "__ab_c__");
+        assert_eq!(tape.trimmed_start(&'a'), -1);
+
+        let blank = Tape::with_head('_', 2, "__".chars());
+        assert_eq!(blank.trimmed(&'_').count(), 0);
+    }
+
+    #[test]
+    fn converts_into_its_cells() {
+        let tape = Tape::with_head('_', -1, "ab".chars());
+        assert_eq!(tape.clone().into_vec(), vec!['_', 'a', 'b']);
+        assert_eq!(Vec::from(tape.clone()), vec!['_', 'a', 'b']);
+        assert_eq!(String::from(tape), "_ab");
+    }
+}
@@ -0,0 +1,562 @@
+//! Traces of runs in JSON Lines.
+//!
+//! ```text
+//! {"state":"A","blank":["_"],"tapes":[{"start":0,"head":0,"cells":["1","1"]}]}
+//! {"step":1,"from":"A","read":["1"],"write":["0"],"dir":["R"],"to":"B","head":[0]}
+//! ```
+//!
+//! The first line is the configuration the run started in: its state, the blank of each tape
+//! and each tape with the index of its first cell, the index of the head and its cells. Every
+//! other line is a transition, with the number of steps taken once it is done, the states before
+//! and after, and for each tape the symbol read, the symbol written, the direction moved in and
+//! the index of the head before moving. States, symbols and directions are written as their
+//! `Label`.
+
+use std::collections::VecDeque;
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt::{ self, Display, Formatter, Write as _ };
+use std::hash::Hash;
+use std::io::{ self, Write };
+
+use crate::{ Dir, Execution, Fuel, Halt, Label, Space, Tape, Tapes, Turing };
+
+impl<S, Mem> Turing<S, Mem>
+    where S: Tapes, S::Sym: Hash, S::Cell: Label, Mem: Label + Clone + Eq + Hash {
+    /// Runs the machine like `run_with_fuel`, writing a trace of the run to `out`.
+    pub fn trace<W: Write>(&self, space: S, fuel: Fuel, out: W) -> io::Result<Halt<S, Mem>> {
+        self.start(space).trace(fuel, out)
+    }
+}
+
+impl<'a, S, Mem> Execution<'a, S, Mem>
+    where S: Tapes, S::Sym: Hash, S::Cell: Label, Mem: Label + Clone + Eq + Hash {
+    /// Runs like `run`, writing a trace starting from the current configuration to `out`.
+    pub fn trace<W: Write>(mut self, fuel: Fuel, mut out: W) -> io::Result<Halt<S, Mem>> {
+        let tapes = self.space().tapes().iter()
+            .map(|tape| format!(r#"{{"start":{},"head":{},"cells":{}}}"#, tape.bounds().start, tape.head(), labels(tape.cells())))
+            .collect::<Vec<_>>();
+        writeln!(out, r#"{{"state":{},"blank":{},"tapes":[{}]}}"#,
+            string(&self.state().label()), labels(S::syms(self.turing().blank())), tapes.join(","))?;
+
+        while !fuel.exhausted(&self.stats()) {
+            let heads: Vec<String> = self.space().tapes().iter().map(|tape| tape.head().to_string()).collect();
+            let step = match self.step() {
+                Some(step) => step,
+                None => break,
+            };
+            writeln!(out, r#"{{"step":{},"from":{},"read":{},"write":{},"dir":{},"to":{},"head":[{}]}}"#,
+                self.stats().steps, string(&step.from.label()), labels(S::syms(&step.read)), labels(S::syms(&step.write)),
+                labels(S::dirs(&step.dir)), string(&step.to.label()), heads.join(","))?;
+        }
+        out.flush()?;
+        Ok(self.into_halt())
+    }
+}
+
+fn labels<'a, T: Label + 'a, I: IntoIterator<Item = &'a T>>(items: I) -> String {
+    let items: Vec<String> = items.into_iter().map(|item| string(&item.label())).collect();
+    format!("[{}]", items.join(","))
+}
+
+fn string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TraceErrorKind {
+    MissingKey(&'static str),
+    Expected(&'static str),
+    Unexpected(String),
+    UnterminatedQuote,
+    Arity { expected: usize, found: usize },
+    /// A step that does not continue from the configuration before it.
+    Inconsistent(String),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TraceError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: TraceErrorKind,
+}
+
+impl Display for TraceErrorKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            TraceErrorKind::MissingKey(key) => write!(f, "missing `{}`", key),
+            TraceErrorKind::Expected(what) => write!(f, "expected {}", what),
+            TraceErrorKind::Unexpected(token) => write!(f, "unexpected `{}`", token),
+            TraceErrorKind::UnterminatedQuote => write!(f, "unterminated quote"),
+            TraceErrorKind::Arity { expected, found } => write!(f, "expected {} blanks, found {}", expected, found),
+            TraceErrorKind::Inconsistent(why) => write!(f, "inconsistent trace: {}", why),
+        }
+    }
+}
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl Error for TraceError {}
+
+/// A configuration rebuilt from a trace, with states and symbols as their labels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Configuration {
+    pub steps: usize,
+    pub state: String,
+    pub tapes: Vec<Tape<String>>,
+}
+
+/// A step of a trace, with states, symbols and directions as their labels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub step: usize,
+    pub from: String,
+    pub read: Vec<String>,
+    pub write: Vec<String>,
+    pub dir: Vec<Dir>,
+    pub to: String,
+    pub head: Vec<isize>,
+}
+
+/// A trace read back without the machine that produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    blanks: Vec<String>,
+    start: Configuration,
+    records: Vec<Record>,
+}
+
+impl Trace {
+    /// Parses a trace, checking that each step continues from the configuration before it.
+    pub fn parse(src: &str) -> Result<Trace, TraceError> {
+        let mut lines = src.lines().enumerate().map(|(i, line)| (i + 1, line)).filter(|(_, line)| !line.trim().is_empty());
+        let (line, first) = lines.next().ok_or(TraceError { line: 1, column: 1, kind: TraceErrorKind::Expected("a configuration") })?;
+        let json = Json::parse(first).map_err(|(column, kind)| TraceError { line, column, kind })?;
+        let (blanks, start) = configuration(&json).map_err(|kind| TraceError { line, column: 1, kind })?;
+
+        let mut trace = Trace { blanks, start, records: Vec::new() };
+        let mut current = trace.start.clone();
+        for (line, src) in lines {
+            let json = Json::parse(src).map_err(|(column, kind)| TraceError { line, column, kind })?;
+            let record = record(&json).map_err(|kind| TraceError { line, column: 1, kind })?;
+            trace.apply(&mut current, &record).map_err(|kind| TraceError { line, column: 1, kind })?;
+            trace.records.push(record);
+        }
+        Ok(trace)
+    }
+
+    pub fn blanks(&self) -> &[String] {
+        &self.blanks
+    }
+
+    pub fn start(&self) -> &Configuration {
+        &self.start
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// The number of steps in the trace.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The configuration after the first `steps` steps of the trace.
+    pub fn configuration(&self, steps: usize) -> Option<Configuration> {
+        self.replay().nth(steps)
+    }
+
+    /// The configuration the trace ends in.
+    pub fn last(&self) -> Configuration {
+        self.replay().last().unwrap()
+    }
+
+    /// The configurations of the trace in order, starting with the initial one.
+    pub fn replay(&self) -> Replay<'_> {
+        Replay { trace: self, current: None, next: 0 }
+    }
+
+    fn apply(&self, config: &mut Configuration, record: &Record) -> Result<(), TraceErrorKind> {
+        let tapes = config.tapes.len();
+        for (what, found) in [("read", record.read.len()), ("write", record.write.len()), ("dir", record.dir.len()), ("head", record.head.len())] {
+            if found != tapes {
+                return Err(TraceErrorKind::Inconsistent(format!("`{}` has {} entries for {} tapes", what, found, tapes)));
+            }
+        }
+        if record.step != config.steps + 1 {
+            return Err(TraceErrorKind::Inconsistent(format!("expected step {}, found {}", config.steps + 1, record.step)));
+        }
+        if record.from != config.state {
+            return Err(TraceErrorKind::Inconsistent(format!("the step starts in `{}` but the machine is in `{}`", record.from, config.state)));
+        }
+
+        for (i, tape) in config.tapes.iter().enumerate() {
+            if record.head[i] != tape.head() {
+                return Err(TraceErrorKind::Inconsistent(format!("the head of tape {} is at {}, not {}", i, tape.head(), record.head[i])));
+            }
+            let sym = tape.read();
+            if record.read[i] != sym {
+                return Err(TraceErrorKind::Inconsistent(format!("tape {} holds `{}` under the head, not `{}`", i, sym, record.read[i])));
+            }
+        }
+
+        for (i, tape) in config.tapes.iter_mut().enumerate() {
+            tape.write(record.write[i].clone());
+            tape.mov(&record.dir[i], &self.blanks[i]);
+        }
+        config.state = record.to.clone();
+        config.steps += 1;
+        Ok(())
+    }
+}
+
+/// An iterator over the configurations of a trace.
+pub struct Replay<'a> {
+    trace: &'a Trace,
+    current: Option<Configuration>,
+    next: usize,
+}
+
+impl<'a> Iterator for Replay<'a> {
+    type Item = Configuration;
+
+    fn next(&mut self) -> Option<Configuration> {
+        let config = match self.current.as_mut() {
+            None => self.current.insert(self.trace.start.clone()),
+            Some(config) => {
+                let record = self.trace.records.get(self.next)?;
+                self.next += 1;
+                // Every record was checked against the configuration before it while parsing.
+                self.trace.apply(config, record).unwrap();
+                config
+            }
+        };
+        Some(config.clone())
+    }
+}
+
+fn configuration(json: &Json) -> Result<(Vec<String>, Configuration), TraceErrorKind> {
+    let state = json.field("state")?.string()?;
+    let blanks = json.field("blank")?.strings()?;
+    let tapes = json.field("tapes")?.array()?.iter()
+        .map(|tape| {
+            let start = tape.field("start")?.int()?;
+            let head = tape.field("head")?.int()?;
+            let cells = tape.field("cells")?.strings()?;
+            if head < start || head >= start + cells.len() as isize {
+                return Err(TraceErrorKind::Inconsistent(format!("the head at {} is outside the tape", head)));
+            }
+            Ok(Tape::from_parts(VecDeque::from(cells), (head - start) as usize, -start))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if blanks.len() != tapes.len() {
+        return Err(TraceErrorKind::Arity { expected: tapes.len(), found: blanks.len() });
+    }
+    Ok((blanks, Configuration { steps: 0, state, tapes }))
+}
+
+fn record(json: &Json) -> Result<Record, TraceErrorKind> {
+    let step = json.field("step")?.int()?;
+    let dir = json.field("dir")?.strings()?.iter()
+        .map(|dir| match dir.as_str() {
+            "L" => Ok(Dir::Left),
+            "S" => Ok(Dir::Stay),
+            "R" => Ok(Dir::Right),
+            d => Err(TraceErrorKind::Unexpected(d.to_owned())),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Record {
+        step: step.try_into().map_err(|_| TraceErrorKind::Expected("a positive step"))?,
+        from: json.field("from")?.string()?,
+        read: json.field("read")?.strings()?,
+        write: json.field("write")?.strings()?,
+        dir,
+        to: json.field("to")?.string()?,
+        head: json.field("head")?.array()?.iter().map(Json::int).collect::<Result<_, _>>()?,
+    })
+}
+
+/// Just enough JSON to read traces back.
+enum Json {
+    Int(isize),
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn parse(src: &str) -> Result<Json, (usize, TraceErrorKind)> {
+        let chars: Vec<char> = src.chars().collect();
+        let mut pos = 0;
+        let json = Json::value(&chars, &mut pos)?;
+        skip_whitespace(&chars, &mut pos);
+        match chars.get(pos) {
+            Some(c) => Err((pos + 1, TraceErrorKind::Unexpected(c.to_string()))),
+            None => Ok(json),
+        }
+    }
+
+    fn value(chars: &[char], pos: &mut usize) -> Result<Json, (usize, TraceErrorKind)> {
+        skip_whitespace(chars, pos);
+        let start = *pos;
+        match chars.get(*pos) {
+            Some('{') => {
+                *pos += 1;
+                let mut fields = Vec::new();
+                if !Json::close(chars, pos, '}') {
+                    loop {
+                        skip_whitespace(chars, pos);
+                        let key = Json::string_at(chars, pos)?;
+                        skip_whitespace(chars, pos);
+                        Json::expect(chars, pos, ':', "`:`")?;
+                        fields.push((key, Json::value(chars, pos)?));
+                        if Json::close(chars, pos, '}') {
+                            break;
+                        }
+                        Json::expect(chars, pos, ',', "`,` or `}`")?;
+                    }
+                }
+                Ok(Json::Obj(fields))
+            }
+            Some('[') => {
+                *pos += 1;
+                let mut items = Vec::new();
+                if !Json::close(chars, pos, ']') {
+                    loop {
+                        items.push(Json::value(chars, pos)?);
+                        if Json::close(chars, pos, ']') {
+                            break;
+                        }
+                        Json::expect(chars, pos, ',', "`,` or `]`")?;
+                    }
+                }
+                Ok(Json::Arr(items))
+            }
+            Some('"') => Json::string_at(chars, pos).map(Json::Str),
+            Some(c) if *c == '-' || c.is_ascii_digit() => {
+                *pos += 1;
+                while chars.get(*pos).is_some_and(char::is_ascii_digit) {
+                    *pos += 1;
+                }
+                let number: String = chars[start..*pos].iter().collect();
+                number.parse().map(Json::Int).map_err(|_| (start + 1, TraceErrorKind::Expected("an integer")))
+            }
+            Some(c) => Err((start + 1, TraceErrorKind::Unexpected(c.to_string()))),
+            None => Err((start + 1, TraceErrorKind::Expected("a value"))),
+        }
+    }
+
+    fn string_at(chars: &[char], pos: &mut usize) -> Result<String, (usize, TraceErrorKind)> {
+        let start = *pos;
+        Json::expect(chars, pos, '"', "a string")?;
+        let mut s = String::new();
+        loop {
+            let c = *chars.get(*pos).ok_or((start + 1, TraceErrorKind::UnterminatedQuote))?;
+            *pos += 1;
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let c = *chars.get(*pos).ok_or((start + 1, TraceErrorKind::UnterminatedQuote))?;
+                    *pos += 1;
+                    s.push(match c {
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'u' => {
+                            let hex: String = chars.get(*pos..*pos + 4).unwrap_or_default().iter().collect();
+                            let c = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+                                .ok_or((*pos + 1, TraceErrorKind::Expected("four hex digits of a character")))?;
+                            *pos += 4;
+                            c
+                        }
+                        c => c,
+                    });
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn expect(chars: &[char], pos: &mut usize, c: char, what: &'static str) -> Result<(), (usize, TraceErrorKind)> {
+        if chars.get(*pos) != Some(&c) {
+            return Err((*pos + 1, TraceErrorKind::Expected(what)));
+        }
+        *pos += 1;
+        Ok(())
+    }
+
+    fn close(chars: &[char], pos: &mut usize, c: char) -> bool {
+        skip_whitespace(chars, pos);
+        let closed = chars.get(*pos) == Some(&c);
+        if closed {
+            *pos += 1;
+        }
+        closed
+    }
+
+    fn field(&self, key: &'static str) -> Result<&Json, TraceErrorKind> {
+        match self {
+            Json::Obj(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, value)| value).ok_or(TraceErrorKind::MissingKey(key)),
+            _ => Err(TraceErrorKind::Expected("an object")),
+        }
+    }
+
+    fn int(&self) -> Result<isize, TraceErrorKind> {
+        match self {
+            Json::Int(i) => Ok(*i),
+            _ => Err(TraceErrorKind::Expected("an integer")),
+        }
+    }
+
+    fn string(&self) -> Result<String, TraceErrorKind> {
+        match self {
+            Json::Str(s) => Ok(s.clone()),
+            _ => Err(TraceErrorKind::Expected("a string")),
+        }
+    }
+
+    fn array(&self) -> Result<&[Json], TraceErrorKind> {
+        match self {
+            Json::Arr(items) => Ok(items),
+            _ => Err(TraceErrorKind::Expected("an array")),
+        }
+    }
+
+    fn strings(&self) -> Result<Vec<String>, TraceErrorKind> {
+        self.array()?.iter().map(Json::string).collect()
+    }
+}
+
+fn skip_whitespace(chars: &[char], pos: &mut usize) {
+    while chars.get(*pos).is_some_and(|c| c.is_whitespace()) {
+        *pos += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ Trace, TraceErrorKind };
+    use crate::{ Fuel, Tape, Tapes, Turing, turing, Dir::* };
+
+    fn trace<S>(t: &Turing<S, char>, space: S) -> String
+        where S: Tapes + Clone, S::Sym: std::hash::Hash + crate::Label, S::Dir: crate::Label, S::Cell: crate::Label {
+        let mut out = Vec::new();
+        t.trace(space, Fuel::steps(1000), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn copy() -> Turing<Tape<char>, char> {
+        turing!('_';'s';'h';
+            ('s') { 'a' => (Right, 'A', 'a') 'b' => (Right, 'B', 'b') '_' => (Left , '_', 'r') },
+            ('a') { 'a' => (Right, 'a', 'a') 'b' => (Right, 'b', 'a') '_' => (Right, '_', 'A') },
+            ('A') { 'a' => (Right, 'a', 'A') 'b' => (Right, 'b', 'A') '_' => (Left , 'a', 'l') },
+            ('b') { 'a' => (Right, 'a', 'b') 'b' => (Right, 'b', 'b') '_' => (Right, '_', 'B') },
+            ('B') { 'a' => (Right, 'a', 'B') 'b' => (Right, 'b', 'B') '_' => (Left , 'b', 'l') },
+            ('l') { 'a' => (Left , 'a', 'l') 'b' => (Left , 'b', 'l') '_' => (Left , '_', 'm') },
+            ('m') { 'a' => (Left , 'a', 'm') 'b' => (Left , 'b', 'm') 'A' => (Right, 'A', 's') 'B' => (Right, 'B', 's') },
+            ('r') { 'A' => (Left , 'a', 'r') 'B' => (Left , 'b', 'r') '_' => (Right, '_', 'h') },
+        )
+    }
+
+    #[test]
+    fn writes_a_configuration_then_each_step() {
+        let t = turing!('_';'a';'h';
+            ('a') { '_' => (Right, '1', 'b') },
+            ('b') { '_' => (Left , '1', 'h') },
+        );
+        let expected = concat!(
+            r#"{"state":"a","blank":["_"],"tapes":[{"start":0,"head":0,"cells":["_"]}]}"#, "\n",
+            r#"{"step":1,"from":"a","read":["_"],"write":["1"],"dir":["R"],"to":"b","head":[0]}"#, "\n",
+            r#"{"step":2,"from":"b","read":["_"],"write":["1"],"dir":["L"],"to":"h","head":[1]}"#, "\n",
+        );
+        assert_eq!(trace(&t, Tape::new('_', None)), expected);
+    }
+
+    #[test]
+    fn replays_to_where_the_run_stopped() {
+        let t = copy();
+        let space = Tape::with_head('_', -1, "abba".chars());
+        let halt = t.run_with_fuel(space.clone(), Fuel::steps(1000));
+        let replayed = Trace::parse(&trace(&t, space)).unwrap();
+        let last = replayed.last();
+        assert_eq!(last.steps, halt.stats().steps);
+        assert_eq!(last.state, halt.mem().to_string());
+        assert_eq!(replayed.len(), halt.stats().steps);
+        let tape = &last.tapes[0];
+        assert_eq!(tape.head(), halt.space().head());
+        assert_eq!(tape.bounds(), halt.space().bounds());
+        assert!(tape.cells().eq(halt.space().cells().map(|c| c.to_string()).collect::<Vec<_>>().iter()));
+        assert_eq!(replayed.configuration(0).as_ref(), Some(replayed.start()));
+    }
+
+    #[test]
+    fn replays_several_tapes() {
+        let t = turing!(['_', '_'];'a';'h';
+            ('a') {
+                ['1', '_'] => ([Right, Right], ['1', '1'], 'a')
+                ['_', '_'] => ([Stay , Left ], ['_', '_'], 'h')
+            },
+        );
+        let space = [Tape::new('_', "111".chars()), Tape::new('_', None)];
+        let halt = t.run(space.clone());
+        let last = Trace::parse(&trace(&t, space)).unwrap().last();
+        for (tape, expected) in last.tapes.iter().zip(halt.space()) {
+            assert_eq!(tape.head(), expected.head());
+            assert!(tape.cells().eq(expected.cells().map(|c| c.to_string()).collect::<Vec<_>>().iter()));
+        }
+    }
+
+    #[test]
+    fn rejects_tampered_traces() {
+        let src = trace(&copy(), Tape::new('_', "ab".chars()));
+        let lines: Vec<&str> = src.lines().collect();
+        let tampered = |line: usize, from: &str, to: &str| {
+            let mut lines = lines.clone();
+            let replaced = lines[line - 1].replacen(from, to, 1);
+            lines[line - 1] = &replaced;
+            Trace::parse(&lines.join("\n")).unwrap_err()
+        };
+
+        let err = tampered(4, r#""from":"a""#, r#""from":"b""#);
+        assert_eq!(err.line, 4);
+        assert!(matches!(err.kind, TraceErrorKind::Inconsistent(_)));
+        let err = tampered(3, r#""head":[1]"#, r#""head":[2]"#);
+        assert_eq!(err.line, 3);
+        assert!(matches!(err.kind, TraceErrorKind::Inconsistent(_)));
+        let err = tampered(5, r#""step":4"#, r#""step":5"#);
+        assert_eq!(err.line, 5);
+        assert!(matches!(err.kind, TraceErrorKind::Inconsistent(_)));
+        let err = tampered(2, r#""read":["a"]"#, r#""read":["b"]"#);
+        assert!(matches!(err.kind, TraceErrorKind::Inconsistent(_)));
+
+        let dropped = lines.iter().enumerate().filter(|&(i, _)| i != 3).map(|(_, l)| *l).collect::<Vec<_>>().join("\n");
+        assert_eq!(Trace::parse(&dropped).unwrap_err().line, 4);
+        assert!(matches!(Trace::parse("").unwrap_err().kind, TraceErrorKind::Expected(_)));
+    }
+}
@@ -0,0 +1,144 @@
+use std::hash::Hash;
+
+use crate::{ Fuel, Tape, Turing, Verdict };
+
+/// A step on which the head reached a cell further out than ever before, with only blanks
+/// beyond it.
+struct Record<Sym, Mem> {
+    step: usize,
+    mem: Mem,
+    tape: Tape<Sym>,
+}
+
+impl<Sym, Mem> Turing<Tape<Sym>, Mem>
+    where Sym: Clone + Eq + Hash, Mem: Clone + Eq + Hash {
+    /// Runs the machine like `run_with_fuel`, but stops once it is caught in a translated cycle:
+    /// repeating the same moves while drifting along the tape.
+    ///
+    /// Every time the head goes further right than ever before onto blank tape, the
+    /// configuration is compared to the earlier such records in the same state. If, relative
+    /// to the head, the two tapes agree on every cell the machine visited between them, the
+    /// machine will do the same from the later record and so forever. The same goes for the
+    /// left. This keeps a tape for every record, so `fuel` should be modest.
+    pub fn detect_translated_cycle(&self, tape: Tape<Sym>, fuel: Fuel) -> Verdict<Tape<Sym>, Mem> {
+        let mut exec = self.start(tape);
+        let mut heads = vec![exec.space().head()];
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        let (mut min, mut max) = (heads[0], heads[0]);
+        loop {
+            let head = exec.space().head();
+            let steps = exec.stats().steps;
+            for (records, dir) in [(&mut right, 1), (&mut left, -1)] {
+                if steps > 0 && head * dir <= if dir > 0 { max } else { -min } {
+                    continue;
+                }
+                if !self.blank_beyond(exec.space(), dir) {
+                    continue;
+                }
+
+                let witness = records.iter().rev()
+                    .filter(|r: &&Record<Sym, Mem>| r.mem == *exec.state())
+                    .find(|r| self.repeats(r, exec.space(), &heads, dir));
+                if let Some(r) = witness {
+                    let (start, shift) = (r.step, head - r.tape.head());
+                    let (space, mem) = (exec.space().clone(), exec.state().clone());
+                    return Verdict::Translated { start, period: steps - start, shift, space, mem };
+                }
+                records.push(Record { step: steps, mem: exec.state().clone(), tape: exec.space().clone() });
+            }
+            min = min.min(head);
+            max = max.max(head);
+
+            if exec.is_halted() || fuel.exhausted(&exec.stats()) {
+                return Verdict::Halted(exec.into_halt());
+            }
+            exec.step();
+            heads.push(exec.space().head());
+        }
+    }
+
+    fn blank_beyond(&self, tape: &Tape<Sym>, dir: isize) -> bool {
+        let bounds = tape.bounds();
+        let mut beyond = if dir > 0 { tape.head() + 1..bounds.end } else { bounds.start..tape.head() };
+        beyond.all(|i| tape.get(i) == Some(&self.default))
+    }
+
+    /// Whether the cells from the head of `tape` back to the furthest the head went since
+    /// `record`, measured from the head of `record`, hold the same symbols as in `record`.
+    fn repeats(&self, record: &Record<Sym, Mem>, tape: &Tape<Sym>, heads: &[isize], dir: isize) -> bool {
+        let from = record.tape.head();
+        let back = heads[record.step..].iter().map(|&head| (from - head) * dir).max().unwrap_or(0);
+        let shift = tape.head() - from;
+        (0..=back).all(|i| {
+            let cell = from - i * dir;
+            let sym = |tape: &Tape<Sym>, cell| tape.get(cell).unwrap_or(&self.default).clone();
+            sym(&record.tape, cell) == sym(tape, cell + shift)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ Fuel, Halt, Tape, Verdict, turing, Dir::* };
+
+    fn translated(verdict: Verdict<Tape<char>, char>) -> (usize, usize, isize) {
+        match verdict {
+            Verdict::Translated { start, period, shift, .. } => (start, period, shift),
+            _ => panic!("expected a translated cycle"),
+        }
+    }
+
+    #[test]
+    fn finds_a_trail_to_the_right() {
+        let t = turing!('_';'a';'h';
+            ('a') { '_' => (Right, '1', 'a') },
+        );
+        assert_eq!(translated(t.detect_translated_cycle(Tape::new('_', None), Fuel::steps(100))), (0, 1, 1));
+    }
+
+    #[test]
+    fn finds_a_trail_to_the_left() {
+        let t = turing!('_';'a';'h';
+            ('a') { '_' => (Left , '1', 'b') },
+            ('b') { '_' => (Left , '_', 'a') },
+        );
+        assert_eq!(translated(t.detect_translated_cycle(Tape::new('_', None), Fuel::steps(100))), (0, 2, -2));
+    }
+
+    #[test]
+    fn finds_a_cycle_that_steps_back() {
+        // Every four steps it writes two cells, going back over the first of them.
+        let t = turing!('_';'a';'h';
+            ('a') { '_' => (Right, '1', 'b') },
+            ('b') { '_' => (Left , '1', 'c') },
+            ('c') { '1' => (Right, '1', 'd') },
+            ('d') { '1' => (Right, '1', 'a') },
+        );
+        assert_eq!(translated(t.detect_translated_cycle(Tape::new('_', None), Fuel::steps(100))), (0, 4, 2));
+    }
+
+    #[test]
+    fn waits_for_the_input_to_be_passed() {
+        let t = turing!('_';'a';'h';
+            ('a') { '1' => (Right, '_', 'a') '_' => (Right, '1', 'a') },
+        );
+        let (start, period, shift) = translated(t.detect_translated_cycle(Tape::new('_', "111".chars()), Fuel::steps(100)));
+        assert!(start >= 3);
+        assert_eq!((period, shift), (1, 1));
+    }
+
+    #[test]
+    fn lets_halting_machines_halt() {
+        let t = turing!('_';'a';'h';
+            ('a') { '_' => (Right, '1', 'b') '1' => (Right, '1', 'h') },
+            ('b') { '_' => (Left , '1', 'a') },
+        );
+        assert!(matches!(t.detect_translated_cycle(Tape::new('_', None), Fuel::steps(100)), Verdict::Halted(Halt::Accepted { .. })));
+        // A binary counter keeps moving further right, but never repeats itself.
+        let counter = turing!('_';'a';'h';
+            ('a') { '_' => (Left , '1', 'b') '0' => (Left , '1', 'b') '1' => (Right, '0', 'a') },
+            ('b') { '0' => (Left , '0', 'b') '1' => (Left , '1', 'b') '_' => (Right, '_', 'a') },
+        );
+        assert!(matches!(counter.detect_translated_cycle(Tape::new('_', None), Fuel::steps(2000)), Verdict::Halted(Halt::OutOfFuel { .. })));
+    }
+}